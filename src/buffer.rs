@@ -1,8 +1,9 @@
 use std::fmt::Debug;
 
-use crate::{PhysicalSize, Res};
+use crate::{image, PhysicalSize, Res};
 
-pub trait Buffer: Debug {
+///Pixel storage behind a `Canvas`. It is `Send + Sync`, so a `World` can be moved to and shared with other threads
+pub trait Buffer: Debug + Send + Sync {
     fn buffer<'a>(&'a mut self) -> &'a mut [u8];
 
     fn clear(&mut self, clear_value: [u8; 4]) {
@@ -33,6 +34,25 @@ impl SimpleBuffer {
         }
     }
 
+    pub fn size(&self) -> PhysicalSize<u32> {
+        PhysicalSize::new(self.width, self.height)
+    }
+
+    ///Creates a SimpleBuffer holding the decoded `png`
+    pub fn from_png(png: &[u8]) -> Res<Self> {
+        let (size, buffer) = image::decode_png(png)?;
+        Ok(Self {
+            width: size.width,
+            height: size.height,
+            buffer,
+        })
+    }
+
+    ///Encodes the buffer as PNG
+    pub fn to_png(&self) -> Res<Vec<u8>> {
+        image::encode_png(self.size(), &self.buffer)
+    }
+
     fn get_pixel(&mut self, x: usize, y: usize) -> Res<&mut [u8; 4]> {
         let index = (self.width as usize * y + x) * 4;
         match self.buffer.get_mut(index..index + 4) {
@@ -142,4 +162,16 @@ mod tests {
 
         buffer.render().unwrap()
     }
+
+    #[test]
+    fn png() -> Res<()> {
+        let mut buffer = SimpleBuffer::new(PhysicalSize::new(2, 3));
+        buffer.clear(Color::new_rgba(10, 20, 30, 255).to_slice());
+        buffer.buffer()[4] = 200;
+
+        let decoded = SimpleBuffer::from_png(&buffer.to_png()?)?;
+        assert_eq!((decoded.width, decoded.height), (2, 3));
+        assert_eq!(decoded.buffer, buffer.buffer);
+        Ok(())
+    }
 }
@@ -1,11 +1,53 @@
 use crate::{math_2d::Vector, position::Position};
 
+///Maps Logicalspace onto Bufferspace.
+///
+///A vector is turned by `rotation_deg` and scaled by `zoom` around the Logicalspace origin, which ends up at `origin` in Bufferspace.
 pub struct Camera {
     origin: Vector,
+    zoom: f64,
+    rotation_deg: f64,
 }
 impl Camera {
     pub fn new(origin: Vector) -> Self {
-        Self { origin }
+        Self {
+            origin,
+            zoom: 1.0,
+            rotation_deg: 0.0,
+        }
+    }
+
+    pub fn set_origin(&mut self, origin: Vector) {
+        self.origin = origin
+    }
+    pub fn get_origin(&self) -> &Vector {
+        &self.origin
+    }
+
+    ///Sets how many pixels one unit of Logicalspace covers. Values above 1 zoom in. `zoom` has to be positive
+    pub fn set_zoom(&mut self, zoom: f64) {
+        assert!(
+            zoom > 0.0,
+            "The zoom of a Camera has to be positive, got {}",
+            zoom
+        );
+        self.zoom = zoom
+    }
+    pub fn get_zoom(&self) -> f64 {
+        self.zoom
+    }
+
+    ///Sets the angle by which Logicalspace appears turned on the screen
+    pub fn set_rotation_deg(&mut self, rotation_deg: f64) {
+        self.rotation_deg = rotation_deg.rem_euclid(360.0)
+    }
+    pub fn get_rotation_deg(&self) -> f64 {
+        self.rotation_deg
+    }
+
+    ///Moves the camera so that `target` in Logicalspace is projected onto `screen_position` in Bufferspace
+    pub fn look_at(&mut self, target: &Vector, screen_position: &Vector) {
+        self.origin = screen_position - target.rotate_degree(self.rotation_deg) * self.zoom;
     }
 
     ///Turns a `vector` in Logicalspace into one in BUfferspace, while clamping it into positive values for `Position`
@@ -22,8 +64,12 @@ impl Camera {
 
     ///Turns a `vector` in Logicalspace into one in Bufferspace
     pub fn project(&self, vector: &Vector) -> Vector {
-        let position = vector + &self.origin;
-        position
+        vector.rotate_degree(self.rotation_deg) * self.zoom + &self.origin
+    }
+
+    ///Turns a `vector` in Bufferspace, like the mouse position, back into one in Logicalspace. Inverse of `project`
+    pub fn unproject(&self, vector: &Vector) -> Vector {
+        ((vector - &self.origin) / self.zoom).rotate_degree(-self.rotation_deg)
     }
 }
 
@@ -38,4 +84,20 @@ mod tests {
 
         assert_eq!(cam.clamped_projection_to_position(&v), Position::new(0, 0));
     }
+
+    #[test]
+    fn zoom_and_rotation() {
+        let mut cam = Camera::new(Vector::new(10.0, 20.0));
+        cam.set_zoom(2.0);
+        cam.set_rotation_deg(90.0);
+
+        let projection = cam.project(&Vector::new(3.0, 0.0));
+        assert!(projection.distance(&Vector::new(10.0, 26.0)) < 1e-9);
+
+        let v = Vector::new(-4.5, 7.25);
+        assert!(cam.unproject(&cam.project(&v)).distance(&v) < 1e-9);
+
+        cam.look_at(&v, &Vector::new(32.0, 32.0));
+        assert!(cam.project(&v).distance(&Vector::new(32.0, 32.0)) < 1e-9);
+    }
 }
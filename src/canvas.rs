@@ -1,6 +1,10 @@
 use crate::PhysicalSize;
 use crate::Res;
 
+use std::path::Path;
+
+use crate::image;
+
 use crate::color::Color;
 
 use crate::buffer::Buffer;
@@ -8,6 +12,8 @@ use crate::buffer::SimpleBuffer;
 
 use crate::position::Position;
 
+use crate::math_2d::Vector;
+
 pub trait Drawable<T> {
     fn set_draw_color(&mut self, color: Color);
 
@@ -26,11 +32,22 @@ pub trait Drawable<T> {
     fn draw_line(&mut self, start: &T, end: &T);
 }
 
+/// Variants describing how lines are rasterized
+///
+/// * `Aliased`: Every pixel is either fully colored or untouched, resulting in jagged edges
+/// * `AntiAliased`: Lines are drawn with the algorithm of Xiaolin Wu. Pixels are blended according to how much the line covers them
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum LineMode {
+    Aliased,
+    AntiAliased,
+}
+
 pub struct Canvas {
     size: PhysicalSize<u32>,
     buffer: Box<dyn Buffer>,
     draw_color: Color,
     fill_color: Color,
+    line_mode: LineMode,
 }
 impl Canvas {
     pub fn new_with_simplebuffer(buffer_size: PhysicalSize<u32>) -> Self {
@@ -44,13 +61,32 @@ impl Canvas {
             buffer,
             draw_color: Color::from_str("black"),
             fill_color: Color::from_str("white"),
+            line_mode: LineMode::Aliased,
         }
     }
 
+    ///Creates a Canvas on a `SimpleBuffer` holding the decoded `png`
+    pub fn new_from_png(png: &[u8]) -> Res<Self> {
+        let buffer = SimpleBuffer::from_png(png)?;
+        Ok(Self::new_from_buffer(buffer.size(), Box::new(buffer)))
+    }
+
     pub fn as_slice(&mut self) -> &mut [u8] {
         self.buffer.buffer()
     }
 
+    ///Encodes the content of the canvas as PNG
+    pub fn to_png(&mut self) -> Res<Vec<u8>> {
+        let size = self.size;
+        image::encode_png(size, self.buffer.buffer())
+    }
+
+    ///Writes the content of the canvas as PNG to `path`
+    pub fn save_png(&mut self, path: impl AsRef<Path>) -> Res<()> {
+        std::fs::write(path, self.to_png()?)?;
+        Ok(())
+    }
+
     fn fill_pixel(buffer: &mut [u8], x: u32, y: u32, buffer_width: u32, color: [u8; 4]) {
         let index = ((buffer_width * y + x) * 4) as usize;
         let color = if color[3] != 255 {
@@ -88,6 +124,75 @@ impl Canvas {
     pub fn get_fill_color(&self) -> Color {
         self.fill_color.clone()
     }
+
+    pub fn set_line_mode(&mut self, line_mode: LineMode) {
+        self.line_mode = line_mode
+    }
+    pub fn get_line_mode(&self) -> LineMode {
+        self.line_mode
+    }
+
+    ///Draws an anti-aliased line in the `draw_color` using the algorithm of Xiaolin Wu.
+    ///
+    ///`start` and `end` are in Bufferspace, but do not have to lie on the canvas or on pixel centers. Parts outside of the canvas are skipped.
+    pub fn draw_smooth_line(&mut self, start: &Vector, end: &Vector) {
+        let color = self.draw_color.to_rgba().to_slice();
+        let width = self.size.width;
+        let height = self.size.height;
+        let buffer = self.buffer.buffer();
+
+        let steep = (end.y - start.y).abs() > (end.x - start.x).abs();
+        let (mut x0, mut y0, mut x1, mut y1) = if steep {
+            (start.y, start.x, end.y, end.x)
+        } else {
+            (start.x, start.y, end.x, end.y)
+        };
+        if x0 > x1 {
+            (x0, x1) = (x1, x0);
+            (y0, y1) = (y1, y0);
+        }
+        let dx = x1 - x0;
+        let gradient = if dx == 0.0 { 1.0 } else { (y1 - y0) / dx };
+
+        let mut plot = |x: f64, y: f64, coverage: f64| {
+            let (x, y) = if steep { (y, x) } else { (x, y) };
+            if x < 0.0 || y < 0.0 || x >= width as f64 || y >= height as f64 {
+                return;
+            }
+            let alpha = (color[3] as f64 * coverage).round() as u8;
+            if alpha > 0 {
+                let pixel = [color[0], color[1], color[2], alpha];
+                Self::fill_pixel(buffer, x as u32, y as u32, width, pixel);
+            }
+        };
+
+        //`fract` keeps the sign, so negative coordinates would get negative coverage
+        let fract = |value: f64| value - value.floor();
+
+        let x_start = x0.round();
+        let y_start = y0 + gradient * (x_start - x0);
+        let gap = 1.0 - fract(x0 + 0.5);
+        plot(x_start, y_start.floor(), (1.0 - fract(y_start)) * gap);
+        plot(x_start, y_start.floor() + 1.0, fract(y_start) * gap);
+
+        let x_end = x1.round();
+        let y_end = y1 + gradient * (x_end - x1);
+        let gap = fract(x1 + 0.5);
+        plot(x_end, y_end.floor(), (1.0 - fract(y_end)) * gap);
+        plot(x_end, y_end.floor() + 1.0, fract(y_end) * gap);
+
+        //Only the part of the major axis lying on the canvas is walked
+        let major_length = if steep { height } else { width } as f64;
+        let mut x = (x_start + 1.0).max(0.0);
+        let x_end = x_end.min(major_length);
+        let mut y = y_start + gradient * (x - x_start);
+        while x < x_end {
+            plot(x, y.floor(), 1.0 - fract(y));
+            plot(x, y.floor() + 1.0, fract(y));
+            y += gradient;
+            x += 1.0;
+        }
+    }
 }
 impl Drawable<Position> for Canvas {
     fn set_draw_color(&mut self, color: Color) {
@@ -124,6 +229,12 @@ impl Drawable<Position> for Canvas {
     }
 
     fn draw_line(&mut self, start: &Position, end: &Position) {
+        if self.line_mode == LineMode::AntiAliased {
+            return self.draw_smooth_line(
+                &Vector::new(start.x as f64, start.y as f64),
+                &Vector::new(end.x as f64, end.y as f64),
+            );
+        }
         let width = self.size.width - 1;
         let height = self.size.height - 1;
         let buffer = self.buffer.buffer();
@@ -175,8 +286,6 @@ impl Drawable<Position> for Canvas {
 
 #[cfg(test)]
 mod tests {
-    use crate::math_2d::Vector;
-
     use super::*;
     use std::fmt::Write;
 
@@ -226,6 +335,37 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn draw_smooth_line() {
+        let size = PhysicalSize::new(5, 4);
+        let mut canvas = Canvas::new_with_simplebuffer(size);
+        canvas.clear(Color::from_str("black").to_slice());
+        canvas.set_draw_color(Color::from_str("white"));
+        canvas.set_line_mode(LineMode::AntiAliased);
+
+        canvas.draw_line(&Position::new(0, 1), &Position::new(4, 1));
+        canvas.draw_smooth_line(&Vector::new(1.0, 2.5), &Vector::new(3.0, 2.5));
+
+        let alpha =
+            |canvas: &mut Canvas, x: u32, y: u32| canvas.as_slice()[((y * 5 + x) * 4 + 3) as usize];
+        let red =
+            |canvas: &mut Canvas, x: u32, y: u32| canvas.as_slice()[((y * 5 + x) * 4) as usize];
+        assert_eq!(red(&mut canvas, 2, 1), 255);
+        assert_eq!(red(&mut canvas, 2, 0), 0);
+        assert!(red(&mut canvas, 0, 1) > 0 && red(&mut canvas, 0, 1) < 255);
+        assert_eq!(red(&mut canvas, 2, 2), red(&mut canvas, 2, 3));
+        assert!(red(&mut canvas, 2, 2) > 100 && red(&mut canvas, 2, 2) < 155);
+        assert_eq!(alpha(&mut canvas, 2, 0), 255);
+
+        canvas.draw_smooth_line(&Vector::new(-10.0, -10.0), &Vector::new(20.0, 20.0));
+        assert_eq!(red(&mut canvas, 3, 3), 255);
+
+        canvas.clear(Color::from_str("black").to_slice());
+        canvas.draw_smooth_line(&Vector::new(-1e12, -0.75), &Vector::new(1e12, -0.75));
+        assert!(red(&mut canvas, 2, 0) > 0 && red(&mut canvas, 2, 0) < 255);
+        assert_eq!(red(&mut canvas, 2, 1), 0);
+    }
+
     #[test]
     #[ignore]
     fn start_end_points() {
@@ -0,0 +1,189 @@
+use std::{fs, path::Path};
+
+use crate::{canvas::Canvas, canvas::Drawable, PhysicalSize, Res};
+
+///Environment variable, that makes `assert_golden_image` overwrite the golden images with the current output instead of comparing against them
+pub const UPDATE_GOLDEN_VAR: &str = "RUSTY_TREE_UPDATE_GOLDEN";
+
+///Encodes a RGBA buffer with the dimensions `size` as PNG
+pub fn encode_png(size: PhysicalSize<u32>, rgba: &[u8]) -> Res<Vec<u8>> {
+    if rgba.len() != (size.width * size.height * 4) as usize {
+        return Err(format!(
+            "Buffer of {} bytes does not fit an image of {}x{} pixels",
+            rgba.len(),
+            size.width,
+            size.height
+        )
+        .into());
+    }
+    let mut png = vec![];
+    {
+        let mut encoder = png::Encoder::new(&mut png, size.width, size.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(rgba)?;
+    }
+    Ok(png)
+}
+
+///Decodes a PNG into its dimensions and a RGBA buffer. Images without alpha channel are treated as opaque
+pub fn decode_png(png: &[u8]) -> Res<(PhysicalSize<u32>, Vec<u8>)> {
+    let mut decoder = png::Decoder::new(png);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer)?;
+    buffer.truncate(info.buffer_size());
+
+    let rgba = match info.color_type {
+        png::ColorType::Rgba => buffer,
+        png::ColorType::Rgb => buffer
+            .chunks(3)
+            .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => buffer
+            .chunks(2)
+            .flat_map(|pixel| [pixel[0], pixel[0], pixel[0], pixel[1]])
+            .collect(),
+        png::ColorType::Grayscale => buffer
+            .iter()
+            .flat_map(|gray| [*gray, *gray, *gray, 255])
+            .collect(),
+        png::ColorType::Indexed => {
+            return Err("Indexed PNGs should have been expanded while decoding".into())
+        }
+    };
+    Ok((PhysicalSize::new(info.width, info.height), rgba))
+}
+
+///Result of comparing two RGBA images of the same size
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageDifference {
+    ///Number of pixels with a channel differing by more than the tolerance
+    pub differing_pixels: usize,
+    ///Biggest difference of a single channel
+    pub max_difference: u8,
+    ///Position of the first differing pixel, going row by row
+    pub first_difference: Option<(u32, u32)>,
+}
+
+///Compares two RGBA images pixel by pixel. Channels differing by at most `tolerance` count as equal
+pub fn compare_images(
+    size: PhysicalSize<u32>,
+    expected: &[u8],
+    actual: &[u8],
+    tolerance: u8,
+) -> ImageDifference {
+    let mut difference = ImageDifference {
+        differing_pixels: 0,
+        max_difference: 0,
+        first_difference: None,
+    };
+    for (index, (expected, actual)) in expected.chunks(4).zip(actual.chunks(4)).enumerate() {
+        let pixel_difference = expected
+            .iter()
+            .zip(actual)
+            .map(|(expected, actual)| expected.abs_diff(*actual))
+            .max()
+            .unwrap_or(0);
+        difference.max_difference = difference.max_difference.max(pixel_difference);
+        if pixel_difference > tolerance {
+            difference.differing_pixels += 1;
+            if difference.first_difference.is_none() {
+                let index = index as u32;
+                difference.first_difference = Some((index % size.width, index / size.width));
+            }
+        }
+    }
+    difference
+}
+
+///Compares the content of `canvas` with the golden image at `path`, allowing every channel to differ by `tolerance`.
+///
+///If the images differ, the content of the canvas is written next to the golden image with the extension `actual.png` for inspection.
+///Setting the environment variable `RUSTY_TREE_UPDATE_GOLDEN` writes the canvas as new golden image instead.
+pub fn assert_golden_image(canvas: &mut Canvas, path: impl AsRef<Path>, tolerance: u8) -> Res<()> {
+    let path = path.as_ref();
+    let size = PhysicalSize::new(canvas.get_width(), canvas.get_height());
+    if std::env::var_os(UPDATE_GOLDEN_VAR).is_some() {
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        return canvas.save_png(path);
+    }
+
+    let (expected_size, expected) = decode_png(&fs::read(path).map_err(|err| {
+        format!(
+            "Could not read golden image {}: {}. Set {} to create it",
+            path.display(),
+            err,
+            UPDATE_GOLDEN_VAR
+        )
+    })?)?;
+    let actual_path = path.with_extension("actual.png");
+    if expected_size.width != size.width || expected_size.height != size.height {
+        canvas.save_png(&actual_path)?;
+        return Err(format!(
+            "Golden image {} is {}x{}, but the canvas is {}x{}",
+            path.display(),
+            expected_size.width,
+            expected_size.height,
+            size.width,
+            size.height
+        )
+        .into());
+    }
+
+    let difference = compare_images(size, &expected, canvas.as_slice(), tolerance);
+    if difference.differing_pixels > 0 {
+        canvas.save_png(&actual_path)?;
+        return Err(format!(
+            "Canvas differs from golden image {} in {} pixels by up to {}, starting at {:?}. The canvas was written to {}",
+            path.display(),
+            difference.differing_pixels,
+            difference.max_difference,
+            difference.first_difference.unwrap(),
+            actual_path.display()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn png_roundtrip() -> Res<()> {
+        let size = PhysicalSize::new(2, 1);
+        let rgba = vec![255, 0, 0, 255, 0, 0, 255, 100];
+
+        let (decoded_size, decoded) = decode_png(&encode_png(size, &rgba)?)?;
+        assert_eq!((decoded_size.width, decoded_size.height), (2, 1));
+        assert_eq!(decoded, rgba);
+
+        assert!(encode_png(PhysicalSize::new(2, 2), &rgba).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn compare() {
+        let size = PhysicalSize::new(2, 2);
+        let expected = vec![0; 16];
+        let mut actual = vec![0; 16];
+        actual[9] = 3;
+        actual[14] = 10;
+
+        let difference = compare_images(size, &expected, &actual, 3);
+        assert_eq!(difference.differing_pixels, 1);
+        assert_eq!(difference.max_difference, 10);
+        assert_eq!(difference.first_difference, Some((1, 1)));
+
+        assert_eq!(
+            compare_images(size, &expected, &actual, 10).differing_pixels,
+            0
+        );
+    }
+}
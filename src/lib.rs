@@ -3,7 +3,7 @@ use std::hash::Hash;
 use std::{cell::RefCell, error::Error, rc::Rc};
 
 use math_2d::Vector;
-use physics_2d::{Body, CollisionBody, RefBody, VisualShape};
+use physics_2d::{Body, CollisionBody, VisualShape};
 use position::Position;
 
 #[derive(Debug, Clone, Copy)]
@@ -25,6 +25,8 @@ pub mod buffer;
 
 pub mod canvas;
 
+pub mod image;
+
 pub mod position;
 
 pub mod camera;
@@ -41,23 +43,3 @@ pub mod physics_2d;
 pub mod pixels_integration;
 
 type Res<T, E = Box<dyn Error>> = Result<T, E>;
-
-mod debug {
-    macro_rules! debug_print {
-        ($func_name:literal $expression:expr) => {
-            #[cfg(not(test))]
-            println!("{}: {:?}", $func_name, $expression);
-        };
-        ($func_name:literal $expression:expr, $($func_names:literal $expressions:expr),+) => {
-            debug_print!($func_name $expression);
-            debug_print!($($func_names $expressions),+);
-        };
-    }
-    pub(crate) use debug_print;
-}
-
-impl RefBody {
-    fn id(&self) -> uuid::Uuid {
-        self.borrow().uuid
-    }
-}
@@ -1,18 +1,21 @@
 #![forbid(unconditional_recursion)]
+use std::error::Error;
+
 use rand::Rng;
 use rusty_tree::{
     camera::Camera,
     canvas::{self, Canvas, Drawable},
     color::Color,
     math_2d::{Intersection, Vector},
-    physics_2d::{Body, BodyBuilder, Shape, VisualShape},
+    physics_2d::{Body, BodyBuilder, BodyHandle, Shape, VisualShape},
+    pixels_integration::{App, Runner},
     position::Position,
     renderer::Renderer,
     world::World,
     PhysicalSize,
 };
 use winit::{
-    event::{Event, WindowEvent},
+    event::{ElementState, MouseButton, WindowEvent},
     event_loop::EventLoop,
     window::WindowBuilder,
 };
@@ -34,7 +37,7 @@ fn main() {
     let builder = WindowBuilder::new().with_title("RustyTree");
     // .with_fullscreen(Some(winit::window::Fullscreen::Borderless(None)));
     let window = builder.build(&event_loop).unwrap();
-    let mut size = window.inner_size();
+    let size = window.inner_size();
 
     let renderer = Renderer::new(
         Camera::new(Vector::new(
@@ -79,69 +82,62 @@ fn main() {
         .build();
     world.add_body(body, None);
 
-    let mut first = true;
-    event_loop.run(move |event, _, control_flow| {
-        control_flow.set_poll();
+    Runner::new(1.0 / 60.0).run(
+        event_loop,
+        window,
+        world,
+        Demo {
+            cursor: Vector::zero(),
+            picked: None,
+        },
+    );
+}
 
+struct Demo {
+    ///Position of the cursor in Logicalspace
+    cursor: Vector,
+    ///The topmost body under the cursor at the last click
+    picked: Option<BodyHandle>,
+}
+impl App for Demo {
+    fn window_event(&mut self, world: &mut World, event: &WindowEvent) {
         match event {
-            Event::WindowEvent {
-                event: WindowEvent::CloseRequested,
-                ..
-            } => {
-                control_flow.set_exit();
+            WindowEvent::Resized(size) => world.renderer.camera.set_origin(Vector::new(
+                size.width as f64 / 2.0,
+                size.height as f64 / 2.0,
+            )),
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor = world
+                    .renderer
+                    .camera
+                    .unproject(&Vector::new(position.x, position.y));
             }
-            Event::WindowEvent {
-                event: WindowEvent::Resized(s),
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
                 ..
             } => {
-                size = s;
-                world.renderer.resize(
-                    PhysicalSize::new(size.width, size.height),
-                    Some(Camera::new(Vector::new(
-                        size.width as f64 / 2.0,
-                        size.height as f64 / 2.0,
-                    ))),
-                );
-                first = true;
+                self.picked = world.pick_handles(&self.cursor).first().copied();
             }
-            Event::WindowEvent {
-                event: WindowEvent::CursorMoved { position, .. },
-                ..
-            } => {}
-            Event::MainEventsCleared => {
-                // Application update code.
-
-                // Queue a RedrawRequested event.
-                //
-                // You only need to call this if you've determined that you need to redraw, in
-                // applications which do not always need to. Applications that redraw continuously
-                // can just render here instead.
-                if first {
-                    first = false;
-                    window.request_redraw();
-                }
-            }
-            Event::RedrawRequested(_) => {
-                // Redraw the application.
-                //
-                // It's preferable for applications that do not render continuously to render in
-                // this event rather than in MainEventsCleared, since rendering in here allows
-                // the program to gracefully handle redraws requested by the OS.
-
-                // let start = Instant::now();
+            _ => {}
+        }
+    }
 
-                // let elapsed = start.elapsed();
-                // println!("Debug: {:?}", elapsed);
+    fn draw(&mut self, world: &mut World, alpha: f64) {
+        let (position, _) = match self
+            .picked
+            .and_then(|handle| world.interpolated_state(handle, alpha))
+        {
+            Some(state) => state,
+            None => return,
+        };
+        let color = world.renderer.get_draw_color();
+        world.renderer.set_draw_color("green".into());
+        world.renderer.draw_ellipse(&position, 10, 10, 0.0);
+        world.renderer.set_draw_color(color);
+    }
 
-                // for pixel in frame.chunks_exact_mut(4) {
-                //     pixel[0] = 0xff; // R
-                //     pixel[1] = 0xff; // G
-                //     pixel[2] = 0xff; // B
-                //     pixel[3] = 0xff; // A
-                // }
-                world.render().unwrap();
-            }
-            _ => (),
-        }
-    })
+    fn render_failed(&mut self, _world: &mut World, error: Box<dyn Error>) {
+        eprintln!("Rendering failed: {}", error);
+    }
 }
@@ -1,8 +1,6 @@
 use std::fmt::{Debug, Display};
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 
-use crate::debug::debug_print;
-
 /// Describes the Intersection between two vectors `v1` and `v2`.
 /// Where the relative Position from `v2` to `v1` is given as `s`
 #[derive(Debug, Clone, PartialEq)]
@@ -15,43 +13,6 @@ pub enum Intersection {
     Line(f64, f64),
 }
 
-#[derive(Debug, Clone, PartialEq)]
-enum SlopeQuadrant {
-    TopRight(f64),
-    TopLeft(f64),
-    BottomLeft(f64),
-    BottomRight(f64),
-}
-impl SlopeQuadrant {
-    fn opposite(&self) -> SlopeQuadrant {
-        match self {
-            Self::BottomLeft(slope) => Self::TopRight(*slope),
-            Self::BottomRight(slope) => Self::TopLeft(*slope),
-            Self::TopLeft(slope) => Self::BottomRight(*slope),
-            Self::TopRight(slope) => Self::BottomLeft(*slope),
-        }
-    }
-
-    fn is_opposite(&self, other: &SlopeQuadrant) -> bool {
-        self.opposite() == *other
-    }
-
-    fn slope(&self) -> f64 {
-        match self {
-            Self::BottomLeft(slope) => *slope,
-            Self::BottomRight(slope) => *slope,
-            Self::TopLeft(slope) => *slope,
-            Self::TopRight(slope) => *slope,
-        }
-    }
-}
-impl Mul for &SlopeQuadrant {
-    type Output = f64;
-    fn mul(self, rhs: Self) -> Self::Output {
-        (if self.is_opposite(&rhs) { -1.0 } else { 1.0 }) * (self.slope() * rhs.slope()).abs()
-    }
-}
-
 #[derive(Clone, Debug)]
 pub struct Vector {
     pub x: f64,
@@ -84,13 +45,8 @@ impl Vector {
         let support_len = support.length();
         let support_fract = support_len / self_len;
 
-        let self_slope = self.slope();
-        let support_slope = support.slope();
-        let other_slope = other.slope();
-        let support_direction = &self_slope * &support_slope > 0.0;
-        let line_direction = &self_slope * &other_slope > 0.0;
-
-        debug_print!("self_len" self_len, "support_len" support_len, "other_len" other_len, "support_fract" support_fract, "self_slope" self_slope, "suppoert_slope" support_slope, "other_slope" other_slope, "suppoert_direction" support_direction, "line_direction" line_direction);
+        let support_direction = self * support >= 0.0;
+        let line_direction = self * other > 0.0;
 
         if line_direction && support_direction {
             //v1.slope = v2.slope && v1.slope = support.slope
@@ -98,10 +54,11 @@ impl Vector {
                 Intersection::None
             } else if self_len == support_len {
                 Intersection::Point(1.0)
-            } else if self_len < other_len {
-                Intersection::Line(support_fract, 1.0)
             } else {
-                Intersection::Line(support_fract, other_len / self_len)
+                Intersection::Line(
+                    support_fract,
+                    ((support_len + other_len) / self_len).min(1.0),
+                )
             }
         } else if !line_direction && !support_direction {
             //v1.slope = - v2.slope && v1.slope = - support.slope
@@ -152,6 +109,12 @@ impl Vector {
     /// gives the factor of self at which the intersection point lies.
     /// If they overlap a 'Line(r1, r2)' will be returned, where the ris mark the
     /// section of self that overlaps.
+    ///
+    /// The segments are solved with cross products, so vertical and horizontal vectors need no special casing.
+    /// Vectors are treated as parallel, if their cross product is below `1e-12` relative to their lengths, and a point
+    /// is treated as lying on a vector within `1e-8` relative to its length. Intersections slightly outside a segment
+    /// due to rounding are clamped onto it. A zero length vector is a single point, that intersects at `Point(0.0)`
+    /// for `self` or at the factor of `self` it lies on for `other`.
     pub fn intersection(&self, other: &Vector, support: &Vector) -> Intersection {
         if other.length() == 0.0 {
             return if support.length() == 0.0 {
@@ -159,74 +122,48 @@ impl Vector {
             } else if self.length() == 0.0 {
                 Intersection::None
             } else {
-                let r = support.x / self.x;
-                if r < 0.0 || r > 1.0 || r - support.y / self.y > 1e-8 {
+                let r = (support * self) / (self * self);
+                if !(0.0..=1.0).contains(&r) || self.cross(support).abs() > 1e-8 * self.length() {
                     Intersection::None
                 } else {
                     Intersection::Point(r)
                 }
             };
         }
-        let mut swapped_vecs = false;
-        debug_print!("self" self, "other" other, "support" support);
-        let (v1, v2) = if other.y == 0.0 {
-            swapped_vecs = true;
-            (other, self)
-        } else {
-            (self, other)
-        };
-        debug_print!("v1" v1, "v2" v2);
-        let s_y = support.y - support.x * v1.y / v1.x;
-        debug_print!("s_y" s_y);
-        let t = if v1.y == 0.0 {
-            -s_y / v2.y
-        } else {
-            let divisor = -v2.y + v1.y * v2.x / v1.x;
-            debug_print!("divisor" divisor);
-            if divisor == 0.0 {
-                f64::INFINITY
-            } else {
-                s_y / divisor
-            }
-        };
-        debug_print!("t" t);
-        if t.is_infinite() {
-            // t is variable || 0 * t = s =/= 0
-            return if s_y != 0.0 {
-                // 0 * t = s
+        if self.length() == 0.0 {
+            let t = -(support * other) / (other * other);
+            return if !(0.0..=1.0).contains(&t)
+                || other.cross(support).abs() > 1e-8 * other.length()
+            {
                 Intersection::None
             } else {
-                // t is variable => Line Overlap
-                self.overlap_area(other, support)
+                Intersection::Point(0.0)
             };
         }
-        if t < 0.0 || t > 1.0 {
-            return Intersection::None;
-        }
-        let r = if v1.x == 0.0 {
-            f64::INFINITY
-        } else {
-            (support.x + v2.x * t) / v1.x
-        };
-        debug_print!("r" r);
-        if r.is_infinite() {
-            // r is variable || 0 * r = s =/= 0
-            return if support.x != 0.0 {
-                // 0 * r = s
+
+        let divisor = self.cross(other);
+        if divisor.abs() <= 1e-12 * self.length() * other.length() {
+            // parallel lines only touch, if they lie on top of each other
+            return if self.cross(support).abs() > 1e-8 * self.length() {
                 Intersection::None
             } else {
-                // r is variable => Line Overlap
                 self.overlap_area(other, support)
             };
         }
-        if r < 0.0 || r > 1.0 {
-            return Intersection::None;
-        }
-        let intersection = r * v1;
-        if (&intersection - (support + t * v2)).length() > 1e-8 {
+        // r * self = support + t * other
+        let r = support.cross(other) / divisor;
+        let t = support.cross(self) / divisor;
+        if !(-1e-12..=1.0 + 1e-12).contains(&r) || !(-1e-12..=1.0 + 1e-12).contains(&t) {
             return Intersection::None;
         }
-        Intersection::Point(if swapped_vecs { t } else { r })
+        Intersection::Point(r.clamp(0.0, 1.0))
+    }
+
+    /// The z-component of the cross product of `self` and `other`, if both were embedded into 3D space.
+    ///
+    /// Positive if `other` is rotated counterclockwise from `self`.
+    pub fn cross(&self, other: &Vector) -> f64 {
+        self.x * other.y - self.y * other.x
     }
 
     pub fn get_intersection_point(&self, intersect: Intersection) -> Vec<Vector> {
@@ -237,27 +174,6 @@ impl Vector {
         }
     }
 
-    fn slope(&self) -> SlopeQuadrant {
-        let mut slope = (self.y / self.x).abs();
-        if slope.is_nan() {
-            slope = f64::INFINITY;
-            // slope = 1.0;
-        }
-        if self.x > 0.0 {
-            if self.y >= 0.0 {
-                SlopeQuadrant::TopRight(slope)
-            } else {
-                SlopeQuadrant::BottomRight(slope)
-            }
-        } else {
-            if self.y >= 0.0 {
-                SlopeQuadrant::TopLeft(slope)
-            } else {
-                SlopeQuadrant::BottomLeft(slope)
-            }
-        }
-    }
-
     pub fn round(&self) -> Self {
         Self::new(self.x.round(), self.y.round())
     }
@@ -265,6 +181,21 @@ impl Vector {
     pub fn abs(&self) -> Self {
         Self::new(self.x.abs(), self.y.abs())
     }
+
+    /// Vector of length 1 pointing in the same direction. The zero vector stays zero
+    pub fn normalize(&self) -> Self {
+        let length = self.length();
+        if length == 0.0 {
+            Self::zero()
+        } else {
+            self / length
+        }
+    }
+
+    /// `self` rotated by 90 degree counterclockwise
+    pub fn perpendicular(&self) -> Self {
+        Self::new(-self.y, self.x)
+    }
 }
 
 impl Default for Vector {
@@ -582,6 +513,19 @@ mod tests {
         assert_eq!(Vector::new(1.0, -1.0).abs(), Vector::new(1.0, 1.0));
     }
 
+    #[test]
+    pub fn normalize() {
+        assert_eq!(Vector::new(3.0, -4.0).normalize(), Vector::new(0.6, -0.8));
+        assert_eq!(Vector::zero().normalize(), Vector::zero());
+    }
+
+    #[test]
+    pub fn perpendicular() {
+        let x = Vector::new(2.0, 1.0);
+        assert_eq!(x.perpendicular(), Vector::new(-1.0, 2.0));
+        assert_eq!(&x * x.perpendicular(), 0.0);
+    }
+
     #[test]
     fn eq() {
         assert!(Vector::scalar(1.0) == Vector::scalar(1.0))
@@ -771,4 +715,83 @@ mod tests {
             Intersection::None
         )
     }
+
+    #[test]
+    fn axis_aligned_intersection() {
+        let horizontal = Vector::new(10.0, 0.0);
+        let vertical = Vector::new(0.0, 10.0);
+
+        assert_eq!(
+            Vector::scalar(10.0).intersection(&horizontal, &Vector::new(0.0, 5.0)),
+            Intersection::Point(0.5)
+        );
+        assert_eq!(
+            vertical.intersection(&horizontal, &Vector::new(-5.0, 5.0)),
+            Intersection::Point(0.5)
+        );
+        assert_eq!(
+            horizontal.intersection(&vertical, &Vector::new(5.0, -5.0)),
+            Intersection::Point(0.5)
+        );
+        assert_eq!(
+            horizontal.intersection(&horizontal, &Vector::new(5.0, 0.0)),
+            Intersection::Line(0.5, 1.0)
+        );
+        assert_eq!(
+            vertical.intersection(&vertical, &Vector::new(0.0, 5.0)),
+            Intersection::Line(0.5, 1.0)
+        );
+        assert_eq!(
+            horizontal.intersection(&horizontal, &Vector::new(5.0, 1.0)),
+            Intersection::None
+        );
+        assert_eq!(
+            vertical.intersection(&Vector::zero(), &Vector::new(0.0, 5.0)),
+            Intersection::Point(0.5)
+        );
+        assert_eq!(
+            Vector::zero().intersection(&vertical, &Vector::new(0.0, -5.0)),
+            Intersection::Point(0.0)
+        );
+    }
+
+    #[test]
+    fn collinear_overlaps() {
+        let horizontal = Vector::new(8.0, 0.0);
+
+        assert_eq!(
+            Vector::new(10.0, 0.0).intersection(&Vector::new(3.0, 0.0), &Vector::new(2.0, 0.0)),
+            Intersection::Line(0.2, 0.5)
+        );
+        assert_eq!(
+            horizontal.intersection(&Vector::new(2.0, 0.0), &Vector::new(4.0, 0.0)),
+            Intersection::Line(0.5, 0.75)
+        );
+        assert_eq!(
+            horizontal.intersection(&horizontal, &Vector::new(4.0, 0.0)),
+            Intersection::Line(0.5, 1.0)
+        );
+        assert_eq!(
+            horizontal.intersection(&Vector::new(-2.0, 0.0), &Vector::new(6.0, 0.0)),
+            Intersection::Line(0.5, 0.75)
+        );
+        assert_eq!(
+            horizontal.intersection(&Vector::new(-4.0, 0.0), &Vector::new(10.0, 0.0)),
+            Intersection::Line(0.75, 1.0)
+        );
+        assert_eq!(
+            horizontal.intersection(&Vector::new(-8.0, 0.0), &Vector::new(4.0, 0.0)),
+            Intersection::Line(0.0, 0.5)
+        );
+    }
+
+    #[test]
+    fn cross() {
+        let x = Vector::new(1.0, 0.0);
+        let y = Vector::new(0.0, 1.0);
+
+        assert_eq!(x.cross(&y), 1.0);
+        assert_eq!(y.cross(&x), -1.0);
+        assert_eq!(x.cross(&x), 0.0);
+    }
 }
@@ -1,14 +1,25 @@
 mod body;
 pub use body::Body;
+pub use body::BodyKind;
 pub use body::VisualShape;
 pub use body::BodyBuilder;
 
+mod body_set;
+pub use body_set::{BodyHandle, BodySet};
+
 mod ref_body;
-pub use ref_body::RefBody;
+pub use ref_body::{BodyRef, BodyRefMut, RefBody};
+pub(crate) use ref_body::LockMarker;
+
+mod force_field;
+pub use force_field::{Drag, ForceField, PointAttractor, WindZone};
+
+mod material;
+pub use material::{CombineRule, Material};
 
 mod collision;
 pub use collision::CollisionLayer;
-pub use collision::{CollisionBody, Mass};
+pub use collision::{CollisionBody, Contact, Mass, RaycastHit};
 
 use crate::math_2d::Vector;
 
@@ -18,6 +29,9 @@ use crate::math_2d::Vector;
 /// * `Ellipse(center: Vector, a: u32, b: u32)`
 /// * `Rect(center: Vector, width: u32, height: u32)`
 /// * `Line(start: Vector, end: Vector)`
+/// * `Polygon(points: Vec<Vector>)`
+///
+/// Rotations turn a shape around its center. For a `Polygon` this is the centroid of its area.
 #[derive(PartialEq, Debug, Clone)]
 pub enum Shape {
     ///Pixel(position: Vector)
@@ -28,6 +42,68 @@ pub enum Shape {
     Rect(Vector, u32, u32),
     ///Line(start: Vector, end: Vector)
     Line(Vector, Vector),
+    ///Polygon(points: Vec<Vector>). Collisions treat it as convex
+    Polygon(Vec<Vector>),
+}
+impl Shape {
+    ///Centroid of the area enclosed by `points`, found with the shoelace formula. Polygons without an area use the average of their points
+    pub(crate) fn polygon_centroid(points: &[Vector]) -> Vector {
+        let average = points.iter().fold(Vector::zero(), |sum, point| sum + point)
+            / points.len().max(1) as f64;
+        let (mut weighted, mut area) = (Vector::zero(), 0.0);
+        for (index, point) in points.iter().enumerate() {
+            // relative to the average to keep the products small
+            let a = point - &average;
+            let b = &points[(index + 1) % points.len()] - &average;
+            let cross = a.cross(&b);
+            weighted += (a + b) * cross;
+            area += cross;
+        }
+        if area.abs() <= 1e-12 {
+            average
+        } else {
+            &average + weighted / (3.0 * area)
+        }
+    }
+
+    ///Points of a polygon turned by `degree` around their centroid and moved by `position`
+    pub(crate) fn polygon_points(points: &[Vector], position: &Vector, degree: f64) -> Vec<Vector> {
+        let center = Shape::polygon_centroid(points);
+        points
+            .iter()
+            .map(|point| position + point.rotate_degree_around(degree, &center))
+            .collect()
+    }
+
+    ///Moment of inertia around the center of the shape, if `mass` is spread evenly over its area.
+    ///
+    ///`Pixel`s have none and `Line`s are treated as thin rods.
+    pub fn moment_of_inertia(&self, mass: f64) -> f64 {
+        match self {
+            Shape::Pixel(..) => 0.0,
+            Shape::Line(start, end) => mass * (end - start) * (end - start) / 12.0,
+            Shape::Rect(_, width, height) => {
+                mass * ((*width as f64).powi(2) + (*height as f64).powi(2)) / 12.0
+            }
+            Shape::Ellipse(_, a, b) => mass * ((*a as f64).powi(2) + (*b as f64).powi(2)) / 4.0,
+            Shape::Polygon(points) => {
+                let center = self.center(&Vector::zero());
+                let (mut weighted, mut area) = (0.0, 0.0);
+                for (index, point) in points.iter().enumerate() {
+                    let a = point - &center;
+                    let b = &points[(index + 1) % points.len()] - &center;
+                    let cross = a.cross(&b);
+                    weighted += cross * (&a * &a + &a * &b + &b * &b);
+                    area += cross;
+                }
+                if area == 0.0 {
+                    0.0
+                } else {
+                    mass * weighted / (6.0 * area)
+                }
+            }
+        }
+    }
 }
 
 impl PartialEq<RefBody> for Body<Vector> {
@@ -39,6 +39,9 @@ impl VisualShape {
             Shape::Ellipse(center, a, b) => {
                 renderer.fill_ellipse(&(position + center), *a, *b, angle_deg)
             }
+            Shape::Polygon(points) => {
+                renderer.fill_polygon(&Shape::polygon_points(points, position, angle_deg))
+            }
         }
     }
 
@@ -58,10 +61,28 @@ impl VisualShape {
             Shape::Ellipse(center, a, b) => {
                 renderer.draw_ellipse(&(position + center), *a, *b, angle_deg)
             }
+            Shape::Polygon(points) => {
+                renderer.draw_polygon(&Shape::polygon_points(points, position, angle_deg))
+            }
         }
     }
 }
 
+/// Variants describing how a `Body` is simulated
+///
+/// * `Dynamic`: Accelerated by forces and impulses and pushed around by collisions
+/// * `Kinematic`: Only moved by code through its velocities. It pushes `Dynamic` bodies, but is unaffected by forces and contacts
+/// * `Static`: Never moves. It is skipped during integration and never updated in its `CollisionLayer`, like level geometry
+///
+/// Bodies that are not `Dynamic` collide like a `CollisionBody` with `Mass::Infinite` and never collide with each other.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum BodyKind {
+    #[default]
+    Dynamic,
+    Kinematic,
+    Static,
+}
+
 #[derive(Debug, Clone)]
 pub struct Body<T> {
     pub mass: f64,
@@ -71,9 +92,17 @@ pub struct Body<T> {
     pub rotation_velocity: f64,
     shape: Option<VisualShape>,
     collision_body: Option<CollisionBody>,
+    kind: BodyKind,
+    ///Sum of the forces acting on the body until the next `integrate`
+    force: T,
+    ///Sum of the torques acting on the body until the next `integrate`
+    torque: f64,
+    sleeping: bool,
+    ///Seconds the body has been moving slower than the sleep thresholds of its `World`
+    resting_time: f64,
     pub uuid: Uuid,
 }
-impl<T> Body<T> {
+impl<T: Default> Body<T> {
     pub fn new(
         mass: f64,
         position: T,
@@ -91,18 +120,181 @@ impl<T> Body<T> {
             rotation_velocity,
             shape,
             collision_body,
+            kind: BodyKind::default(),
+            force: T::default(),
+            torque: 0.0,
+            sleeping: false,
+            resting_time: 0.0,
             uuid: Uuid::new_v4(),
         }
     }
-
+}
+impl<T> Body<T> {
     pub fn has_collision(&self) -> bool {
         self.collision_body.is_some()
     }
+
+    pub(crate) fn collision_body(&self) -> Option<&CollisionBody> {
+        self.collision_body.as_ref()
+    }
+
+    pub fn kind(&self) -> BodyKind {
+        self.kind
+    }
+
+    ///Changes the `BodyKind`. A body turned `Static` stays in the cells of its `CollisionLayer` it was in before
+    pub fn set_kind(&mut self, kind: BodyKind) {
+        self.kind = kind
+    }
+
+    pub fn is_dynamic(&self) -> bool {
+        self.kind == BodyKind::Dynamic
+    }
+
+    ///Sleeping bodies are skipped by `World::step` until they are touched by a moving body or a force or impulse is applied to them.
+    ///
+    ///Changing the velocities of a sleeping body directly does not wake it, call `wake_up` for that.
+    pub fn is_sleeping(&self) -> bool {
+        self.sleeping
+    }
+
+    pub fn wake_up(&mut self) {
+        if self.sleeping {
+            self.sleeping = false;
+            self.resting_time = 0.0;
+        }
+    }
+
+    ///Only `Dynamic` bodies with a positive `mass` are accelerated by forces and impulses
+    fn responds_to_forces(&self) -> bool {
+        self.is_dynamic() && self.mass > 0.0
+    }
 }
 impl Body<Vector> {
+    ///Accelerates the body by the accumulated forces and torques, then moves it along its `velocity` and turns it by its `rotation_velocity` for the timespan `dt`.
+    ///
+    ///Velocities are given per second, so `dt` is expected in seconds. `angle_deg` is kept in `[0, 360)`.
+    ///Only `Dynamic` bodies with a positive `mass` are accelerated and `Static` ones are not moved at all. The accumulated forces and torques are reset afterwards.
+    pub fn integrate(&mut self, dt: f64) {
+        let force = std::mem::take(&mut self.force);
+        let torque = std::mem::take(&mut self.torque);
+        if self.kind == BodyKind::Static {
+            return;
+        }
+        if self.responds_to_forces() {
+            self.velocity += force * (dt / self.mass);
+        }
+        let inertia = self.inertia();
+        if inertia > 0.0 {
+            self.rotation_velocity += (torque / inertia).to_degrees() * dt;
+        }
+        self.position += &self.velocity * dt;
+        self.angle_deg = (self.angle_deg + self.rotation_velocity * dt).rem_euclid(360.0);
+    }
+
+    ///Whether the body may move into others, so it has to be tested for collisions.
+    ///
+    ///Sleeping and `Static` bodies never do and `Kinematic` ones only while they have a velocity.
+    pub(crate) fn is_awake(&self) -> bool {
+        match self.kind {
+            BodyKind::Dynamic => !self.sleeping,
+            BodyKind::Kinematic => self.velocity != Vector::zero() || self.rotation_velocity != 0.0,
+            BodyKind::Static => false,
+        }
+    }
+
+    ///Adds `dt` to the time the body is resting, if its velocities do not exceed `max_velocity` and `max_rotation_velocity`. Otherwise the time is reset.
+    ///
+    ///Returns the time the body has been resting.
+    pub(crate) fn rest(&mut self, dt: f64, max_velocity: f64, max_rotation_velocity: f64) -> f64 {
+        if self.velocity.length() <= max_velocity
+            && self.rotation_velocity.abs() <= max_rotation_velocity
+        {
+            self.resting_time += dt;
+        } else {
+            self.resting_time = 0.0;
+        }
+        self.resting_time
+    }
+
+    ///Stops the body and puts it to sleep
+    pub(crate) fn fall_asleep(&mut self) {
+        self.sleeping = true;
+        self.velocity = Vector::zero();
+        self.rotation_velocity = 0.0;
+        self.force = Vector::zero();
+        self.torque = 0.0;
+    }
+
+    ///The shape of its `CollisionBody`, or the visible one if it has none
+    fn physical_shape(&self) -> Option<&Shape> {
+        match (&self.collision_body, &self.shape) {
+            (Some(collision_body), _) => Some(collision_body.shape()),
+            (None, Some(visual_shape)) => Some(&visual_shape.shape),
+            (None, None) => None,
+        }
+    }
+
+    ///The point the body turns around, which is the center of its shape, or its `position` if it has none
+    pub fn center_of_mass(&self) -> Vector {
+        match self.physical_shape() {
+            Some(shape) => shape.center(&self.position),
+            None => self.position.clone(),
+        }
+    }
+
+    ///Moment of inertia of the body around its `center_of_mass`, derived from its `mass` and shape. Zero for bodies without a shape or that do not respond to forces
+    pub fn inertia(&self) -> f64 {
+        match self.physical_shape() {
+            Some(shape) if self.responds_to_forces() => shape.moment_of_inertia(self.mass),
+            _ => 0.0,
+        }
+    }
+
+    ///Applies `force` at the `center_of_mass` during the next `integrate`. Forces add up until then. Wakes the body up
+    pub fn apply_force(&mut self, force: &Vector) {
+        self.wake_up();
+        self.force += force;
+    }
+
+    ///Applies `torque` during the next `integrate`. Positive torques increase `angle_deg`. Torques add up until then. Wakes the body up
+    pub fn apply_torque(&mut self, torque: f64) {
+        self.wake_up();
+        self.torque += torque;
+    }
+
+    ///Changes the `velocity` instantly by `impulse` divided by the `mass`. Has no effect on bodies, that are not `Dynamic` or have no positive `mass`. Wakes the body up
+    pub fn apply_impulse(&mut self, impulse: &Vector) {
+        self.wake_up();
+        if self.responds_to_forces() {
+            self.velocity += impulse / self.mass;
+        }
+    }
+
+    ///Like `apply_impulse`, but the `impulse` hits the body at `point` in worldspace, so it also changes the `rotation_velocity`
+    pub fn apply_impulse_at_point(&mut self, impulse: &Vector, point: &Vector) {
+        self.apply_impulse(impulse);
+        let inertia = self.inertia();
+        if inertia > 0.0 {
+            let lever = point - self.center_of_mass();
+            self.rotation_velocity += (lever.cross(impulse) / inertia).to_degrees();
+        }
+    }
+
+    ///Checks whether `point` lies inside the body. The shape of its `CollisionBody` is used, or the visible one if it has none
+    pub fn contains(&self, point: &Vector) -> bool {
+        self.physical_shape()
+            .is_some_and(|shape| shape.contains(&self.position, self.angle_deg, point))
+    }
+
     pub fn render(&self, renderer: &mut Renderer) {
+        self.render_at(renderer, &self.position, self.angle_deg)
+    }
+
+    ///Renders the body as if it was at `position` and turned by `angle_deg`
+    pub fn render_at(&self, renderer: &mut Renderer, position: &Vector, angle_deg: f64) {
         if let Some(shape) = &self.shape {
-            shape.render(renderer, self.angle_deg, &self.position)
+            shape.render(renderer, angle_deg, position)
         }
     }
 }
@@ -136,6 +328,7 @@ pub struct BodyBuilder<T> {
     pub rotation_velocity: f64,
     shape: Option<VisualShape>,
     collision_body: Option<CollisionBody>,
+    kind: BodyKind,
 }
 impl<T: Default> BodyBuilder<T> {
     pub fn new() -> Self {
@@ -147,6 +340,7 @@ impl<T: Default> BodyBuilder<T> {
             rotation_velocity: 0.0,
             shape: None,
             collision_body: None,
+            kind: BodyKind::default(),
         }
     }
 }
@@ -160,9 +354,25 @@ impl<T: Clone> BodyBuilder<T> {
             rotation_velocity: 0.0,
             shape: None,
             collision_body: None,
+            kind: BodyKind::default(),
         }
     }
 }
+impl<T: Default> BodyBuilder<T> {
+    pub fn build(self) -> Body<T> {
+        let mut body = Body::new(
+            self.mass,
+            self.position,
+            self.velocity,
+            self.angle_deg,
+            self.rotation_velocity,
+            self.shape,
+            self.collision_body,
+        );
+        body.kind = self.kind;
+        body
+    }
+}
 impl<T> BodyBuilder<T> {
     pub fn mass(mut self, mass: f64) -> Self {
         self.mass = mass;
@@ -199,16 +409,9 @@ impl<T> BodyBuilder<T> {
         self
     }
 
-    pub fn build(self) -> Body<T> {
-        Body::new(
-            self.mass,
-            self.position,
-            self.velocity,
-            self.angle_deg,
-            self.rotation_velocity,
-            self.shape,
-            self.collision_body,
-        )
+    pub fn kind(mut self, kind: BodyKind) -> Self {
+        self.kind = kind;
+        self
     }
 }
 
@@ -235,4 +438,155 @@ mod tests {
         assert_eq!(b1, &b3);
         assert_eq!(&b1, &b3);
     }
+
+    #[test]
+    fn integrate() {
+        let mut body = BodyBuilder::new()
+            .velocity(Vector::new(10.0, -4.0))
+            .angle_deg(350.0)
+            .rotation_velocity(40.0)
+            .build();
+
+        body.integrate(0.5);
+        assert_eq!(body.position, Vector::new(5.0, -2.0));
+        assert_eq!(body.angle_deg, 10.0);
+
+        body.rotation_velocity = -40.0;
+        body.integrate(0.5);
+        assert_eq!(body.position, Vector::new(10.0, -4.0));
+        assert_eq!(body.angle_deg, 350.0);
+    }
+
+    #[test]
+    fn integrate_forces() {
+        let mut body = BodyBuilder::new().mass(2.0).build();
+        body.apply_force(&Vector::new(4.0, 0.0));
+        body.apply_force(&Vector::new(0.0, -2.0));
+
+        body.integrate(0.5);
+        assert_eq!(body.velocity, Vector::new(1.0, -0.5));
+        assert_eq!(body.position, Vector::new(0.5, -0.25));
+
+        body.integrate(0.5);
+        assert_eq!(body.velocity, Vector::new(1.0, -0.5));
+
+        let mut massless = BodyBuilder::new().build();
+        massless.apply_force(&Vector::new(4.0, 0.0));
+        massless.integrate(1.0);
+        assert_eq!(massless.velocity, Vector::zero());
+    }
+
+    #[test]
+    fn body_kinds() {
+        let body = |kind: BodyKind| {
+            BodyBuilder::new()
+                .mass(1.0)
+                .velocity(Vector::new(2.0, 0.0))
+                .rotation_velocity(10.0)
+                .shape(Shape::Rect(Vector::zero(), 2, 2), true)
+                .kind(kind)
+                .build()
+        };
+        let mut kinematic = body(BodyKind::Kinematic);
+        assert!(!kinematic.is_dynamic());
+        kinematic.apply_force(&Vector::new(0.0, 10.0));
+        kinematic.apply_torque(5.0);
+        kinematic.apply_impulse_at_point(&Vector::new(0.0, 1.0), &Vector::new(1.0, 0.0));
+        assert_eq!(kinematic.inertia(), 0.0);
+        kinematic.integrate(1.0);
+        assert_eq!(kinematic.velocity, Vector::new(2.0, 0.0));
+        assert_eq!(kinematic.rotation_velocity, 10.0);
+        assert_eq!(kinematic.position, Vector::new(2.0, 0.0));
+        assert_eq!(kinematic.angle_deg, 10.0);
+
+        let mut fixed = body(BodyKind::Static);
+        fixed.apply_force(&Vector::new(0.0, 10.0));
+        fixed.integrate(1.0);
+        assert_eq!(fixed.position, Vector::zero());
+        assert_eq!(fixed.angle_deg, 0.0);
+
+        fixed.set_kind(BodyKind::Dynamic);
+        assert_eq!(fixed.kind(), BodyKind::Dynamic);
+        fixed.integrate(1.0);
+        assert_eq!(fixed.velocity, Vector::new(2.0, 0.0));
+    }
+
+    #[test]
+    fn inertia() {
+        let body = |shape: Shape| BodyBuilder::new().mass(3.0).shape(shape, true).build();
+        assert_eq!(body(Shape::Rect(Vector::zero(), 2, 4)).inertia(), 5.0);
+        assert_eq!(body(Shape::Ellipse(Vector::zero(), 2, 2)).inertia(), 6.0);
+        assert_eq!(
+            body(Shape::Line(Vector::zero(), Vector::new(0.0, 2.0))).inertia(),
+            1.0
+        );
+        let square = Shape::Polygon(vec![
+            Vector::new(-1.0, -1.0),
+            Vector::new(1.0, -1.0),
+            Vector::new(1.0, 1.0),
+            Vector::new(-1.0, 1.0),
+        ]);
+        assert!((body(square).inertia() - 2.0).abs() < 1e-9);
+        assert_eq!(body(Shape::Pixel(Vector::zero())).inertia(), 0.0);
+        assert_eq!(BodyBuilder::new().mass(3.0).build().inertia(), 0.0);
+    }
+
+    #[test]
+    fn polygon_centroid() {
+        // Right triangle with legs of 6 and 3 and extra points along its long leg, so the average of the points is off
+        let points = vec![
+            Vector::new(0.0, 0.0),
+            Vector::new(2.0, 0.0),
+            Vector::new(4.0, 0.0),
+            Vector::new(6.0, 0.0),
+            Vector::new(0.0, 3.0),
+        ];
+        let body = BodyBuilder::new()
+            .mass(3.0)
+            .position(Vector::new(10.0, 10.0))
+            .shape(Shape::Polygon(points.clone()), true)
+            .build();
+
+        assert!(body.center_of_mass().distance(&Vector::new(12.0, 11.0)) < 1e-9);
+        // m * (a² + b²) / 18 around the centroid
+        assert!((body.inertia() - 7.5).abs() < 1e-9, "{}", body.inertia());
+
+        let turned = Shape::polygon_points(&points, &Vector::zero(), 180.0);
+        assert!(
+            turned[0].distance(&Vector::new(4.0, 2.0)) < 1e-9,
+            "{}",
+            turned[0]
+        );
+        assert!(
+            turned[4].distance(&Vector::new(4.0, -1.0)) < 1e-9,
+            "{}",
+            turned[4]
+        );
+    }
+
+    #[test]
+    fn impulses_and_torque() {
+        let mut body = BodyBuilder::new()
+            .mass(2.0)
+            .position(Vector::new(10.0, 10.0))
+            .shape(Shape::Rect(Vector::new(1.0, 0.0), 2, 4), true)
+            .build();
+        assert_eq!(body.center_of_mass(), Vector::new(11.0, 10.0));
+
+        body.apply_impulse(&Vector::new(1.0, 0.0));
+        assert_eq!(body.velocity, Vector::new(0.5, 0.0));
+        assert_eq!(body.rotation_velocity, 0.0);
+
+        body.apply_impulse_at_point(&Vector::new(0.0, 1.0), &Vector::new(12.0, 10.0));
+        assert_eq!(body.velocity, Vector::new(0.5, 0.5));
+        assert!((body.rotation_velocity - 0.3f64.to_degrees()).abs() < 1e-9);
+
+        body.rotation_velocity = 0.0;
+        body.apply_torque(10.0);
+        body.apply_torque(-5.0);
+        body.integrate(0.5);
+        assert!((body.rotation_velocity - 0.75f64.to_degrees()).abs() < 1e-9);
+        body.integrate(0.5);
+        assert!((body.rotation_velocity - 0.75f64.to_degrees()).abs() < 1e-9);
+    }
 }
@@ -0,0 +1,202 @@
+use std::ops::{Index, IndexMut};
+
+use crate::math_2d::Vector;
+
+use super::Body;
+
+///Generational index of a body inside a `BodySet`.
+///
+///A handle stays invalid once its body got removed, even after the slot is reused by another body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BodyHandle {
+    index: u32,
+    generation: u32,
+}
+
+#[derive(Debug, Clone)]
+struct Slot {
+    generation: u32,
+    body: Option<Body<Vector>>,
+}
+
+///Arena storing bodies by value, which are addressed by `BodyHandle`s.
+///
+///Bodies are plain values here, so the set can be sent to and shared with other threads and borrowing never fails at runtime.
+#[derive(Debug, Clone, Default)]
+pub struct BodySet {
+    slots: Vec<Slot>,
+    free_slots: Vec<u32>,
+    len: usize,
+}
+impl BodySet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///Stores the `body` in a free slot and returns its handle
+    pub fn insert(&mut self, body: Body<Vector>) -> BodyHandle {
+        self.len += 1;
+        match self.free_slots.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.body = Some(body);
+                BodyHandle {
+                    index,
+                    generation: slot.generation,
+                }
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    body: Some(body),
+                });
+                BodyHandle {
+                    index: self.slots.len() as u32 - 1,
+                    generation: 0,
+                }
+            }
+        }
+    }
+
+    ///Removes the body of `handle` and returns it. Every handle to it gets invalid
+    pub fn remove(&mut self, handle: BodyHandle) -> Option<Body<Vector>> {
+        let slot = self.slots.get_mut(handle.index as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        let body = slot.body.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free_slots.push(handle.index);
+        self.len -= 1;
+        Some(body)
+    }
+
+    pub fn contains(&self, handle: BodyHandle) -> bool {
+        self.get(handle).is_some()
+    }
+
+    pub fn get(&self, handle: BodyHandle) -> Option<&Body<Vector>> {
+        self.slots
+            .get(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)?
+            .body
+            .as_ref()
+    }
+
+    pub fn get_mut(&mut self, handle: BodyHandle) -> Option<&mut Body<Vector>> {
+        self.slots
+            .get_mut(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)?
+            .body
+            .as_mut()
+    }
+
+    ///Mutable references to two different bodies at once. `None` if a handle is invalid or both are the same
+    pub fn get_pair_mut(
+        &mut self,
+        handle: BodyHandle,
+        other: BodyHandle,
+    ) -> Option<(&mut Body<Vector>, &mut Body<Vector>)> {
+        if handle.index == other.index || !self.contains(handle) || !self.contains(other) {
+            return None;
+        }
+        let (low, high) = (handle.index.min(other.index), handle.index.max(other.index));
+        let (head, tail) = self.slots.split_at_mut(high as usize);
+        let low_body = head[low as usize].body.as_mut()?;
+        let high_body = tail[0].body.as_mut()?;
+        if handle.index < other.index {
+            Some((low_body, high_body))
+        } else {
+            Some((high_body, low_body))
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    ///Iterates over all bodies in the order of their slots
+    pub fn iter(&self) -> impl Iterator<Item = (BodyHandle, &Body<Vector>)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            slot.body.as_ref().map(|body| {
+                (
+                    BodyHandle {
+                        index: index as u32,
+                        generation: slot.generation,
+                    },
+                    body,
+                )
+            })
+        })
+    }
+
+    ///Iterates mutably over all bodies in the order of their slots
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (BodyHandle, &mut Body<Vector>)> {
+        self.slots
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, slot)| {
+                let generation = slot.generation;
+                slot.body.as_mut().map(|body| {
+                    (
+                        BodyHandle {
+                            index: index as u32,
+                            generation,
+                        },
+                        body,
+                    )
+                })
+            })
+    }
+}
+impl Index<BodyHandle> for BodySet {
+    type Output = Body<Vector>;
+
+    fn index(&self, handle: BodyHandle) -> &Body<Vector> {
+        self.get(handle)
+            .expect("The BodyHandle is not valid anymore")
+    }
+}
+impl IndexMut<BodyHandle> for BodySet {
+    fn index_mut(&mut self, handle: BodyHandle) -> &mut Body<Vector> {
+        self.get_mut(handle)
+            .expect("The BodyHandle is not valid anymore")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(mass: f64) -> Body<Vector> {
+        Body::new(mass, Vector::zero(), Vector::zero(), 0.0, 0.0, None, None)
+    }
+
+    #[test]
+    fn generational_handles() {
+        let mut bodies = BodySet::new();
+        let first = bodies.insert(body(1.0));
+        let second = bodies.insert(body(2.0));
+        assert_eq!(bodies.len(), 2);
+        assert_eq!(bodies[second].mass, 2.0);
+
+        assert_eq!(bodies.remove(first).map(|body| body.mass), Some(1.0));
+        assert!(bodies.remove(first).is_none());
+        let third = bodies.insert(body(3.0));
+        assert_ne!(first, third);
+        assert!(bodies.get(first).is_none());
+        assert_eq!(bodies[third].mass, 3.0);
+        assert_eq!(bodies.len(), 2);
+
+        let (b3, b2) = bodies.get_pair_mut(third, second).unwrap();
+        b3.mass = 30.0;
+        b2.mass = 20.0;
+        assert!(bodies.get_pair_mut(second, second).is_none());
+        let masses: Vec<f64> = bodies.iter().map(|(_, body)| body.mass).collect();
+        assert_eq!(masses, vec![30.0, 20.0]);
+    }
+}
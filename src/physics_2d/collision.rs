@@ -1,14 +1,144 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::f64::consts::PI;
 
 use crate::math_2d::Vector;
-use crate::physics_2d::{RefBody, Shape};
+use crate::physics_2d::{Body, BodyHandle, BodySet, Material, RefBody, Shape};
 use crate::spatial_hashgrid::SpatialHashgrid;
 use crate::PhysicalSize;
-use uuid::Uuid;
 
-///`Rect` and `Ellipse` are considered to be hollow for collision
+///`Rect` and `Ellipse` are considered to be hollow for collision, unless they belong to a solid `CollisionBody`
+///
+///All intersection points are returned in worldspace
 type CollisionShape = Shape;
 impl CollisionShape {
+    ///Start and end of a `Line` at `pos`, rotated by `degree` around its center
+    fn line_points(start: &Vector, end: &Vector, pos: &Vector, degree: f64) -> (Vector, Vector) {
+        let center = (start + end) / 2.0;
+        (
+            pos + start.rotate_degree_around(degree, &center),
+            pos + end.rotate_degree_around(degree, &center),
+        )
+    }
+
+    ///Corners of a `Rect` at `pos`, rotated by `degree` around its center. Neighbouring corners share an edge
+    fn rect_corners(
+        center: &Vector,
+        width: u32,
+        height: u32,
+        pos: &Vector,
+        degree: f64,
+    ) -> [Vector; 4] {
+        let center = pos + center;
+        let w = width as f64 / 2.0;
+        let h = height as f64 / 2.0;
+        [
+            Vector::new(-w, -h),
+            Vector::new(w, -h),
+            Vector::new(w, h),
+            Vector::new(-w, h),
+        ]
+        .map(|corner| &center + corner.rotate_degree(degree))
+    }
+
+    fn intersect_segments(
+        start: &Vector,
+        end: &Vector,
+        other_start: &Vector,
+        other_end: &Vector,
+    ) -> Vec<Vector> {
+        let v1 = end - start;
+        let v2 = other_end - other_start;
+        let support = other_start - start;
+        let intersect = v1.intersection(&v2, &support);
+        v1.get_intersection_point(intersect)
+            .into_iter()
+            .map(|point| point + start)
+            .collect()
+    }
+
+    fn intersect_segment_rect(start: &Vector, end: &Vector, corners: &[Vector; 4]) -> Vec<Vector> {
+        let mut intersections = vec![];
+        for i in 0..corners.len() {
+            intersections.append(&mut CollisionShape::intersect_segments(
+                start,
+                end,
+                &corners[i],
+                &corners[(i + 1) % corners.len()],
+            ));
+        }
+        intersections
+    }
+
+    ///Solves the intersection in the frame of the ellipse, where it is a unit circle
+    fn intersect_segment_ellipse(
+        start: &Vector,
+        end: &Vector,
+        center: &Vector,
+        a: f64,
+        b: f64,
+        degree: f64,
+    ) -> Vec<Vector> {
+        let to_unit_circle = |vector: Vector| {
+            let vector = vector.rotate_degree(-degree);
+            Vector::new(vector.x / a, vector.y / b)
+        };
+        let edge = end - start;
+        let s = to_unit_circle(start - center);
+        let d = to_unit_circle(edge.clone());
+
+        let quadratic = &d * &d;
+        if quadratic == 0.0 {
+            return if ((&s * &s) - 1.0).abs() < 1e-6 {
+                vec![start.clone()]
+            } else {
+                vec![]
+            };
+        }
+        let linear = 2.0 * (&s * &d);
+        let constant = &s * &s - 1.0;
+        let discriminant = linear.powi(2) - 4.0 * quadratic * constant;
+        if discriminant < -1e-9 * linear.powi(2).max(quadratic) {
+            return vec![];
+        }
+        let root = discriminant.max(0.0).sqrt();
+        let mut factors = vec![(-linear - root) / (2.0 * quadratic)];
+        if root > 0.0 {
+            factors.push((-linear + root) / (2.0 * quadratic));
+        }
+        factors
+            .into_iter()
+            .filter(|r| (0.0..=1.0).contains(r))
+            .map(|r| start + r * &edge)
+            .collect()
+    }
+
+    ///Distance of `point` to the closest point of the segment from `start` to `end`
+    fn segment_distance(start: &Vector, end: &Vector, point: &Vector) -> f64 {
+        let edge = end - start;
+        let length = &edge * &edge;
+        let t = if length == 0.0 {
+            0.0
+        } else {
+            ((point - start) * &edge / length).clamp(0.0, 1.0)
+        };
+        (start + t * &edge).distance(point)
+    }
+
+    ///Evaluates the implicit equation of the ellipse at `point`. Negative inside, zero on the outline and positive outside
+    fn ellipse_value(point: &Vector, center: &Vector, a: f64, b: f64, degree: f64) -> f64 {
+        let local = (point - center).rotate_degree(-degree);
+        (local.x / a).powi(2) + (local.y / b).powi(2) - 1.0
+    }
+
+    fn is_on_ellipse(point: &Vector, center: &Vector, a: f64, b: f64, degree: f64) -> bool {
+        CollisionShape::ellipse_value(point, center, a, b, degree).abs() < 1e-6
+    }
+
+    ///Point on the outline of the ellipse at the parameter `t` in radians
+    fn ellipse_point(center: &Vector, a: f64, b: f64, degree: f64, t: f64) -> Vector {
+        center + Vector::new(a * t.cos(), b * t.sin()).rotate_degree(degree)
+    }
+
     fn intersect_line_line(
         l1: &CollisionShape,
         l1_pos: &Vector,
@@ -19,21 +149,12 @@ impl CollisionShape {
     ) -> Vec<Vector> {
         match l1 {
             CollisionShape::Line(start, end) => {
-                let center = (start + end) / 2.0;
-                let start = start.rotate_degree_around(l1_degree, &center);
-                let end = end.rotate_degree_around(l1_degree, &center);
-                let v1 = end - &start;
-
-                let support_base = l2_pos - l1_pos - start;
+                let (start, end) = CollisionShape::line_points(start, end, l1_pos, l1_degree);
                 match l2 {
                     CollisionShape::Line(other_start, other_end) => {
-                        let center = (other_start + other_end) / 2.0;
-                        let other_start = other_start.rotate_degree_around(l2_degree, &center);
-                        let other_end = other_end.rotate_degree_around(l2_degree, &center);
-                        let support = &other_start + support_base;
-                        let v2 = other_end - other_start;
-                        let intersect = v1.intersection(&v2, &support);
-                        v1.get_intersection_point(intersect)
+                        let (other_start, other_end) =
+                            CollisionShape::line_points(other_start, other_end, l2_pos, l2_degree);
+                        CollisionShape::intersect_segments(&start, &end, &other_start, &other_end)
                     }
                     x => unreachable!(
                         "Only Expects 'Line' in 'intersect_line_line', but encountered {:?}",
@@ -56,17 +177,11 @@ impl CollisionShape {
     ) -> Vec<Vector> {
         match l {
             CollisionShape::Line(start, end) => {
-                let center = (start + end) / 2.0;
-                let start = start.rotate_degree_around(l_degree, &center);
-                let end = end.rotate_degree_around(l_degree, &center);
-                let v1 = end - &start;
-
-                let support_base = p_pos - l_pos - start;
+                let (start, end) = CollisionShape::line_points(start, end, l_pos, l_degree);
                 match p {
                     Self::Pixel(pos) => {
-                        let support = pos + support_base;
-                        let intersect = v1.intersection(&Vector::zero(), &support);
-                        v1.get_intersection_point(intersect)
+                        let pos = pos + p_pos;
+                        CollisionShape::intersect_segments(&start, &end, &pos, &pos)
                     }
                     x => unreachable!(
                         "Only Expects 'Pixel' in 'intersect_line_pixel', but encountered {:?}",
@@ -90,45 +205,12 @@ impl CollisionShape {
     ) -> Vec<Vector> {
         match l {
             CollisionShape::Line(start, end) => {
-                let center = (start + end) / 2.0;
-                let start = start.rotate_degree_around(l_degree, &center);
-                let end = end.rotate_degree_around(l_degree, &center);
-                let v1 = end - &start;
-
-                let support_base = r_pos - l_pos - start;
+                let (start, end) = CollisionShape::line_points(start, end, l_pos, l_degree);
                 match r {
                     Self::Rect(center, width, height) => {
-                        let main_diagonal = Vector::new(*width as f64, *height as f64);
-                        let off_diagonal = Vector::new(-(*width as f64), *height as f64);
-
-                        //Basic Corners
-                        let bottom_right = (center + &main_diagonal).rotate_degree(r_degree);
-                        let top_left = (center - main_diagonal).rotate_degree(r_degree);
-                        let bottom_left = (center + &off_diagonal).rotate_degree(r_degree);
-                        let top_right = (center - off_diagonal).rotate_degree(r_degree);
-
-                        let support = &bottom_left + &support_base;
-                        let mut intersection_points = vec![];
-
-                        let bottom_intersection =
-                            v1.intersection(&(&bottom_right - &bottom_left), &support);
-                        intersection_points
-                            .append(&mut v1.get_intersection_point(bottom_intersection));
-                        let left_intersection =
-                            v1.intersection(&(&top_left - &bottom_left), &support);
-                        intersection_points
-                            .append(&mut v1.get_intersection_point(left_intersection));
-
-                        let support = &top_right + &support_base;
-
-                        let top_intersection = v1.intersection(&(&top_left - &top_right), &support);
-                        intersection_points
-                            .append(&mut v1.get_intersection_point(top_intersection));
-                        let right_intersection =
-                            v1.intersection(&(&bottom_right - &top_right), &support);
-                        intersection_points
-                            .append(&mut v1.get_intersection_point(right_intersection));
-                        intersection_points
+                        let corners =
+                            CollisionShape::rect_corners(center, *width, *height, r_pos, r_degree);
+                        CollisionShape::intersect_segment_rect(&start, &end, &corners)
                     }
                     x => unreachable!(
                         "Only Expects 'Rect' in 'intersect_line_rect', but encountered {:?}",
@@ -152,66 +234,16 @@ impl CollisionShape {
     ) -> Vec<Vector> {
         match l {
             CollisionShape::Line(start, end) => {
-                let center = (start + end) / 2.0;
-                let start = start.rotate_degree_around(l_degree, &center);
-                let end = end.rotate_degree_around(l_degree, &center);
-                let v1 = end - &start;
-
-                let support_base = e_pos - l_pos - start;
+                let (start, end) = CollisionShape::line_points(start, end, l_pos, l_degree);
                 match e {
-                    Self::Ellipse(center, a, b) => {
-                        let center = center + support_base;
-                        let mut a = *a as f64;
-                        let mut b = *b as f64;
-                        let mut alpha = e_degree;
-
-                        if a < b {
-                            (a, b) = (b, a);
-                            alpha -= 90.0;
-                        }
-
-                        let focal_distance = (a.powi(2) - b.powi(2)).sqrt();
-                        let focal_offset = if alpha != e_degree {
-                            Vector::new(0.0, focal_distance)
-                        } else {
-                            Vector::new(focal_distance, 0.0)
-                        }
-                        .rotate_degree(e_degree);
-
-                        let f1 = &center + &focal_offset;
-                        let f2 = &center - &focal_offset;
-
-                        let point_on_ellipse = center + Vector::new(0.0, b).rotate_degree(alpha);
-                        let distance =
-                            point_on_ellipse.distance(&f1) + point_on_ellipse.distance(&f2);
-
-                        let calc_distance = |vector: &Vector| {
-                            vector.distance(&f1) + vector.distance(&f2) - distance
-                        };
-
-                        let mut intersections = vec![];
-                        let mut open_intervalls = vec![(Vector::zero(), v1)];
-                        while let Some((start, end)) = open_intervalls.pop() {
-                            let edge = &end - &start;
-                            let step = &edge / edge.length();
-                            let center = &edge / 2.0;
-                            let left = calc_distance(&(&center - &step));
-                            let right = calc_distance(&(&center + &step));
-                            let distance = calc_distance(&center);
-
-                            if left < distance {
-                                open_intervalls.push((start, center.clone()));
-                            }
-                            if right < distance {
-                                open_intervalls.push((center.clone(), end));
-                            }
-                            if distance < 1e-6 {
-                                intersections.push(center);
-                            }
-                        }
-
-                        intersections
-                    }
+                    Self::Ellipse(center, a, b) => CollisionShape::intersect_segment_ellipse(
+                        &start,
+                        &end,
+                        &(e_pos + center),
+                        *a as f64,
+                        *b as f64,
+                        e_degree,
+                    ),
                     x => unreachable!(
                         "Only Expects 'Ellipse' in 'intersect_line_ellipse', but encountered {:?}",
                         x
@@ -265,22 +297,12 @@ impl CollisionShape {
                 let pos = pos + p_pos;
                 match r {
                     Self::Rect(center, width, height) => {
-                        let width = *width as f64;
-                        let height = *height as f64;
-                        let center = r_pos + center;
-                        let p_rot = pos.rotate_degree_around(-r_degree, &center);
-                        if (((p_rot.x - center.x + width).abs() < 1e-6
-                            || (p_rot.x - center.x - width).abs() < 1e-6)
-                            && p_rot.y > center.y - height
-                            && p_rot.y < center.y + height)
-                            || (((p_rot.y - center.y + height).abs() < 1e-6
-                                || (p_rot.y - center.y - height).abs() < 1e-6)
-                                && p_rot.x > center.x - width
-                                && p_rot.x < center.x + width)
-                        {
-                            vec![p_rot]
-                        } else {
+                        let corners =
+                            CollisionShape::rect_corners(center, *width, *height, r_pos, r_degree);
+                        if CollisionShape::intersect_segment_rect(&pos, &pos, &corners).is_empty() {
                             vec![]
+                        } else {
+                            vec![pos]
                         }
                     }
                     x => unreachable!(
@@ -305,38 +327,16 @@ impl CollisionShape {
         match p {
             CollisionShape::Pixel(pos) => {
                 let pos = pos + p_pos;
-                let support_base = e_pos - &pos;
                 match e {
                     Self::Ellipse(center, a, b) => {
-                        let center = center + support_base;
-                        let mut a = *a as f64;
-                        let mut b = *b as f64;
-                        let mut alpha = e_degree;
-
-                        if a < b {
-                            (a, b) = (b, a);
-                            alpha -= 90.0;
-                        }
-
-                        let focal_distance = (a.powi(2) - b.powi(2)).sqrt();
-                        let focal_offset = if alpha != e_degree {
-                            Vector::new(0.0, focal_distance)
-                        } else {
-                            Vector::new(focal_distance, 0.0)
-                        }
-                        .rotate_degree(e_degree);
-
-                        let f1 = &center + &focal_offset;
-                        let f2 = &center - &focal_offset;
-
-                        let point_on_ellipse = &center + Vector::new(0.0, b).rotate_degree(alpha);
-                        let distance =
-                            point_on_ellipse.distance(&f1) + point_on_ellipse.distance(&f2);
-
-                        let p_rot = pos.rotate_degree_around(-e_degree, &center);
-                        let distance = p_rot.distance(&f1) + p_rot.distance(&f2) - distance;
-                        if distance.abs() < 1e-6 {
-                            vec![p_rot]
+                        if CollisionShape::is_on_ellipse(
+                            &pos,
+                            &(e_pos + center),
+                            *a as f64,
+                            *b as f64,
+                            e_degree,
+                        ) {
+                            vec![pos]
                         } else {
                             vec![]
                         }
@@ -363,49 +363,25 @@ impl CollisionShape {
     ) -> Vec<Vector> {
         match r1 {
             CollisionShape::Rect(center, width, height) => {
-                let main_diagonal = Vector::new(*width as f64, *height as f64);
-                let off_diagonal = Vector::new(-(*width as f64), *height as f64);
-
-                //Basic Corners
-                let bottom_right = (center + &main_diagonal).rotate_degree(r1_degree);
-                let top_left = (center - main_diagonal).rotate_degree(r1_degree);
-                let bottom_left = (center + &off_diagonal).rotate_degree(r1_degree);
-                let top_right = (center - off_diagonal).rotate_degree(r1_degree);
+                let corners =
+                    CollisionShape::rect_corners(center, *width, *height, r1_pos, r1_degree);
                 match r2 {
-                    Self::Rect(..) => {
-                        let mut intersections = vec![];
-                        intersections.append(&mut CollisionShape::intersect_line_rect(
-                            &Shape::Line(Vector::zero(), bottom_left.clone()),
-                            &bottom_right,
-                            0.0,
-                            r2,
-                            r2_pos,
-                            r2_degree,
-                        ));
-                        intersections.append(&mut CollisionShape::intersect_line_rect(
-                            &Shape::Line(Vector::zero(), top_right.clone()),
-                            &bottom_right,
-                            0.0,
-                            r2,
-                            r2_pos,
-                            r2_degree,
-                        ));
-                        intersections.append(&mut CollisionShape::intersect_line_rect(
-                            &Shape::Line(Vector::zero(), bottom_left.clone()),
-                            &top_left,
-                            0.0,
-                            r2,
-                            r2_pos,
-                            r2_degree,
-                        ));
-                        intersections.append(&mut CollisionShape::intersect_line_rect(
-                            &Shape::Line(Vector::zero(), top_right.clone()),
-                            &top_left,
-                            0.0,
-                            r2,
+                    Self::Rect(other_center, other_width, other_height) => {
+                        let other_corners = CollisionShape::rect_corners(
+                            other_center,
+                            *other_width,
+                            *other_height,
                             r2_pos,
                             r2_degree,
-                        ));
+                        );
+                        let mut intersections = vec![];
+                        for i in 0..corners.len() {
+                            intersections.append(&mut CollisionShape::intersect_segment_rect(
+                                &corners[i],
+                                &corners[(i + 1) % corners.len()],
+                                &other_corners,
+                            ));
+                        }
                         intersections
                     }
                     x => unreachable!(
@@ -430,53 +406,26 @@ impl CollisionShape {
     ) -> Vec<Vector> {
         match r {
             CollisionShape::Rect(center, width, height) => {
-                let main_diagonal = Vector::new(*width as f64, *height as f64);
-                let off_diagonal = Vector::new(-(*width as f64), *height as f64);
-
-                //Basic Corners
-                let bottom_right = (center + &main_diagonal).rotate_degree(r_degree);
-                let top_left = (center - main_diagonal).rotate_degree(r_degree);
-                let bottom_left = (center + &off_diagonal).rotate_degree(r_degree);
-                let top_right = (center - off_diagonal).rotate_degree(r_degree);
+                let corners =
+                    CollisionShape::rect_corners(center, *width, *height, r_pos, r_degree);
                 match e {
-                    Self::Ellipse(..) => {
+                    Self::Ellipse(e_center, a, b) => {
+                        let e_center = e_pos + e_center;
                         let mut intersections = vec![];
-                        intersections.append(&mut CollisionShape::intersect_line_ellipse(
-                            &Shape::Line(Vector::zero(), bottom_left.clone()),
-                            &bottom_right,
-                            0.0,
-                            e,
-                            e_pos,
-                            e_degree,
-                        ));
-                        intersections.append(&mut CollisionShape::intersect_line_ellipse(
-                            &Shape::Line(Vector::zero(), top_right.clone()),
-                            &bottom_right,
-                            0.0,
-                            e,
-                            e_pos,
-                            e_degree,
-                        ));
-                        intersections.append(&mut CollisionShape::intersect_line_ellipse(
-                            &Shape::Line(Vector::zero(), bottom_left.clone()),
-                            &top_left,
-                            0.0,
-                            e,
-                            e_pos,
-                            e_degree,
-                        ));
-                        intersections.append(&mut CollisionShape::intersect_line_ellipse(
-                            &Shape::Line(Vector::zero(), top_right.clone()),
-                            &top_left,
-                            0.0,
-                            e,
-                            e_pos,
-                            e_degree,
-                        ));
+                        for i in 0..corners.len() {
+                            intersections.append(&mut CollisionShape::intersect_segment_ellipse(
+                                &corners[i],
+                                &corners[(i + 1) % corners.len()],
+                                &e_center,
+                                *a as f64,
+                                *b as f64,
+                                e_degree,
+                            ));
+                        }
                         intersections
                     }
                     x => unreachable!(
-                        "Only Expects 'Rect' in 'intersect_rect_ellipse', but encountered {:?}",
+                        "Only Expects 'Ellipse' in 'intersect_rect_ellipse', but encountered {:?}",
                         x
                     ),
                 }
@@ -498,9 +447,82 @@ impl CollisionShape {
         match e1 {
             CollisionShape::Ellipse(c1, a1, b1) => match e2 {
                 Self::Ellipse(c2, a2, b2) => {
-                    let mut intersections = vec![];
-                    intersections;
-                    todo!("ELI ELI STUFF")
+                    let c1 = e1_pos + c1;
+                    let c2 = e2_pos + c2;
+                    let (a1, b1) = (*a1 as f64, *b1 as f64);
+                    let (a2, b2) = (*a2 as f64, *b2 as f64);
+                    // walks along the outline of e1 and evaluates e2 there. Roots are intersections
+                    let value = |t: f64| {
+                        CollisionShape::ellipse_value(
+                            &CollisionShape::ellipse_point(&c1, a1, b1, e1_degree, t),
+                            &c2,
+                            a2,
+                            b2,
+                            e2_degree,
+                        )
+                    };
+                    let bisect = |mut low: f64, mut high: f64| {
+                        let low_sign = value(low).signum();
+                        for _ in 0..64 {
+                            let middle = (low + high) / 2.0;
+                            if value(middle).signum() == low_sign {
+                                low = middle;
+                            } else {
+                                high = middle;
+                            }
+                        }
+                        (low + high) / 2.0
+                    };
+                    // golden section search for the smallest absolute value between low and high
+                    let minimize = |mut low: f64, mut high: f64| {
+                        let ratio = (5.0_f64.sqrt() - 1.0) / 2.0;
+                        for _ in 0..80 {
+                            let left = high - ratio * (high - low);
+                            let right = low + ratio * (high - low);
+                            if value(left).abs() < value(right).abs() {
+                                high = right;
+                            } else {
+                                low = left;
+                            }
+                        }
+                        (low + high) / 2.0
+                    };
+
+                    const SAMPLES: usize = 360;
+                    let step = 2.0 * PI / SAMPLES as f64;
+                    let samples: Vec<f64> = (0..SAMPLES).map(|i| value(i as f64 * step)).collect();
+
+                    let mut parameters = vec![];
+                    for i in 0..SAMPLES {
+                        let t = i as f64 * step;
+                        let previous = samples[(i + SAMPLES - 1) % SAMPLES];
+                        let current = samples[i];
+                        let next = samples[(i + 1) % SAMPLES];
+                        if current == 0.0 {
+                            parameters.push(t);
+                        } else if current * next < 0.0 {
+                            parameters.push(bisect(t, t + step));
+                        } else if current * previous > 0.0
+                            && current * next > 0.0
+                            && current.abs() <= previous.abs()
+                            && current.abs() <= next.abs()
+                        {
+                            // the outlines might touch or cross twice between two samples
+                            let closest = minimize(t - step, t + step);
+                            let closest_value = value(closest);
+                            if closest_value.abs() < 1e-9 {
+                                parameters.push(closest);
+                            } else if closest_value * current < 0.0 {
+                                parameters.push(bisect(t - step, closest));
+                                parameters.push(bisect(closest, t + step));
+                            }
+                        }
+                    }
+
+                    parameters
+                        .into_iter()
+                        .map(|t| CollisionShape::ellipse_point(&c1, a1, b1, e1_degree, t))
+                        .collect()
                 }
                 x => unreachable!(
                     "Only Expects 'Ellipse' in 'intersect_ellipse_ellipse', but encountered {:?}",
@@ -514,6 +536,547 @@ impl CollisionShape {
         }
     }
 
+    fn intersect_polygon(
+        p: &CollisionShape,
+        p_pos: &Vector,
+        p_degree: f64,
+        s: &CollisionShape,
+        s_pos: &Vector,
+        s_degree: f64,
+    ) -> Vec<Vector> {
+        match p {
+            CollisionShape::Polygon(points) => {
+                if p.separating_axis(p_pos, p_degree, s, s_pos, s_degree)
+                    .is_none()
+                {
+                    return vec![];
+                }
+                let points = Shape::polygon_points(points, p_pos, p_degree);
+                let mut intersections = vec![];
+                for i in 0..points.len() {
+                    let start = &points[i];
+                    let end = &points[(i + 1) % points.len()];
+                    intersections.append(&mut match s {
+                        Self::Pixel(position) => {
+                            let position = s_pos + position;
+                            CollisionShape::intersect_segments(start, end, &position, &position)
+                        }
+                        Self::Line(line_start, line_end) => {
+                            let (line_start, line_end) =
+                                CollisionShape::line_points(line_start, line_end, s_pos, s_degree);
+                            CollisionShape::intersect_segments(start, end, &line_start, &line_end)
+                        }
+                        Self::Rect(center, width, height) => {
+                            let corners = CollisionShape::rect_corners(
+                                center, *width, *height, s_pos, s_degree,
+                            );
+                            CollisionShape::intersect_segment_rect(start, end, &corners)
+                        }
+                        Self::Ellipse(center, a, b) => CollisionShape::intersect_segment_ellipse(
+                            start,
+                            end,
+                            &(s_pos + center),
+                            *a as f64,
+                            *b as f64,
+                            s_degree,
+                        ),
+                        Self::Polygon(other_points) => {
+                            let other_points = Shape::polygon_points(other_points, s_pos, s_degree);
+                            let mut intersections = vec![];
+                            for j in 0..other_points.len() {
+                                intersections.append(&mut CollisionShape::intersect_segments(
+                                    start,
+                                    end,
+                                    &other_points[j],
+                                    &other_points[(j + 1) % other_points.len()],
+                                ));
+                            }
+                            intersections
+                        }
+                    });
+                }
+                intersections
+            }
+            x => unreachable!(
+                "Only Expects 'Polygon' in 'intersect_polygon', but encountered {:?}",
+                x
+            ),
+        }
+    }
+
+    ///Corners of shapes with straight edges in worldspace. `None` for an `Ellipse`
+    fn vertices(&self, pos: &Vector, degree: f64) -> Option<Vec<Vector>> {
+        match self {
+            Self::Pixel(position) => Some(vec![pos + position]),
+            Self::Line(start, end) => {
+                let (start, end) = CollisionShape::line_points(start, end, pos, degree);
+                Some(vec![start, end])
+            }
+            Self::Rect(center, width, height) => {
+                Some(CollisionShape::rect_corners(center, *width, *height, pos, degree).to_vec())
+            }
+            Self::Polygon(points) => Some(Shape::polygon_points(points, pos, degree)),
+            Self::Ellipse(..) => None,
+        }
+    }
+
+    ///Searches an axis separating the filled shapes (SAT). Returns `None` if there is one.
+    ///
+    ///The tested axes are the edge normals of both shapes and, for an `Ellipse`, the direction to the closest corner of the other shape.
+    ///Otherwise the unit normal pointing from `self` to `other` with the smallest overlap and that overlap are returned.
+    ///The result is exact for convex shapes with straight edges. With an `Ellipse` only a found separation is exact.
+    fn separating_axis(
+        &self,
+        self_pos: &Vector,
+        self_degree: f64,
+        other: &CollisionShape,
+        other_pos: &Vector,
+        other_degree: f64,
+    ) -> Option<(Vector, f64)> {
+        let self_vertices = self.vertices(self_pos, self_degree);
+        let other_vertices = other.vertices(other_pos, other_degree);
+        let mut axes = vec![];
+        for (vertices, center, opposite) in [
+            (&self_vertices, self.center(self_pos), &other_vertices),
+            (&other_vertices, other.center(other_pos), &self_vertices),
+        ] {
+            match (vertices, opposite) {
+                (Some(vertices), _) if vertices.len() > 1 => {
+                    let edges = if vertices.len() == 2 {
+                        1
+                    } else {
+                        vertices.len()
+                    };
+                    for i in 0..edges {
+                        axes.push(
+                            (&vertices[(i + 1) % vertices.len()] - &vertices[i]).perpendicular(),
+                        );
+                    }
+                }
+                (None, Some(opposite)) => {
+                    if let Some(closest) = opposite
+                        .iter()
+                        .min_by(|p1, p2| p1.distance(&center).total_cmp(&p2.distance(&center)))
+                    {
+                        axes.push(closest - &center);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut best: Option<(Vector, f64)> = None;
+        for axis in axes {
+            let axis = axis.normalize();
+            if axis == Vector::zero() {
+                continue;
+            }
+            let self_max = &self.support(self_pos, self_degree, &axis) * &axis;
+            let self_min = &self.support(self_pos, self_degree, &-&axis) * &axis;
+            let other_max = &other.support(other_pos, other_degree, &axis) * &axis;
+            let other_min = &other.support(other_pos, other_degree, &-&axis) * &axis;
+            let forward = self_max - other_min;
+            let backward = other_max - self_min;
+            if forward < 0.0 || backward < 0.0 {
+                return None;
+            }
+            let (normal, overlap) = if forward <= backward {
+                (axis, forward)
+            } else {
+                (-axis, backward)
+            };
+            if best.as_ref().is_none_or(|(_, depth)| overlap < *depth) {
+                best = Some((normal, overlap));
+            }
+        }
+        Some(best.unwrap_or_else(|| (Vector::new(1.0, 0.0), 0.0)))
+    }
+
+    ///Checks whether `point` in worldspace lies inside the filled shape.
+    ///
+    ///`Pixel`s and `Line`s have no area, so they contain every point closer than half a unit.
+    pub(crate) fn contains(&self, pos: &Vector, degree: f64, point: &Vector) -> bool {
+        match self {
+            Self::Pixel(position) => (pos + position).distance(point) <= 0.5,
+            Self::Line(start, end) => {
+                let (start, end) = CollisionShape::line_points(start, end, pos, degree);
+                CollisionShape::segment_distance(&start, &end, point) <= 0.5
+            }
+            Self::Ellipse(center, a, b) => {
+                CollisionShape::ellipse_value(point, &(pos + center), *a as f64, *b as f64, degree)
+                    <= 0.0
+            }
+            Self::Rect(..) | Self::Polygon(..) => self
+                .separating_axis(
+                    pos,
+                    degree,
+                    &Shape::Pixel(point.clone()),
+                    &Vector::zero(),
+                    0.0,
+                )
+                .is_some(),
+        }
+    }
+
+    ///Checks whether the filled shapes overlap, either because their outlines intersect or because one contains the other
+    pub(crate) fn overlaps(
+        &self,
+        self_pos: &Vector,
+        self_degree: f64,
+        other: &CollisionShape,
+        other_pos: &Vector,
+        other_degree: f64,
+    ) -> bool {
+        let direction = Vector::new(1.0, 0.0);
+        self.intersection(self_pos, self_degree, other, other_pos, other_degree)
+            .is_some()
+            || self.contains(
+                self_pos,
+                self_degree,
+                &other.support(other_pos, other_degree, &direction),
+            )
+            || other.contains(
+                other_pos,
+                other_degree,
+                &self.support(self_pos, self_degree, &direction),
+            )
+    }
+
+    ///Casts a ray from `origin` along the unit vector `direction` for at most `max_distance` against the outline of the shape.
+    ///
+    ///Returns the closest hit as its point, the unit normal of the outline facing the ray and the distance from `origin`.
+    ///A ray starting inside a shape hits it where it leaves the shape.
+    pub(crate) fn raycast(
+        &self,
+        pos: &Vector,
+        degree: f64,
+        origin: &Vector,
+        direction: &Vector,
+        max_distance: f64,
+    ) -> Option<(Vector, Vector, f64)> {
+        let ray = Shape::Line(origin.clone(), origin + direction * max_distance);
+        let point = ray
+            .intersection(&Vector::zero(), 0.0, self, pos, degree)?
+            .into_iter()
+            .min_by(|p1, p2| p1.distance(origin).total_cmp(&p2.distance(origin)))?;
+        let mut normal = self.outline_normal(pos, degree, &point);
+        if normal == Vector::zero() {
+            normal = -direction;
+        } else if &normal * direction > 0.0 {
+            normal = -normal;
+        }
+        let distance = point.distance(origin);
+        Some((point, normal, distance))
+    }
+
+    ///Unit normal of the outline at `point` in worldspace, which is expected to lie on it. Zero for a `Pixel`
+    fn outline_normal(&self, pos: &Vector, degree: f64, point: &Vector) -> Vector {
+        if let Self::Ellipse(center, a, b) = self {
+            let (a, b) = (*a as f64, *b as f64);
+            let local = (point - (pos + center)).rotate_degree(-degree);
+            return Vector::new(local.x / (a * a), local.y / (b * b))
+                .rotate_degree(degree)
+                .normalize();
+        }
+        let vertices = self.vertices(pos, degree).unwrap_or_default();
+        let edges = match vertices.len() {
+            0 | 1 => return Vector::zero(),
+            2 => 1,
+            n => n,
+        };
+        (0..edges)
+            .map(|i| (&vertices[i], &vertices[(i + 1) % vertices.len()]))
+            .min_by(|(s1, e1), (s2, e2)| {
+                CollisionShape::segment_distance(s1, e1, point)
+                    .total_cmp(&CollisionShape::segment_distance(s2, e2, point))
+            })
+            .map(|(start, end)| (end - start).perpendicular().normalize())
+            .unwrap()
+    }
+
+    ///Center of the shape in worldspace
+    pub(crate) fn center(&self, pos: &Vector) -> Vector {
+        pos + match self {
+            Self::Pixel(position) => position.clone(),
+            Self::Line(start, end) => (start + end) / 2.0,
+            Self::Rect(center, ..) => center.clone(),
+            Self::Ellipse(center, ..) => center.clone(),
+            Self::Polygon(points) => Shape::polygon_centroid(points),
+        }
+    }
+
+    ///Radius of the smallest circle around the center containing the shape. `None` for shapes without an area
+    fn area_radius(&self) -> Option<f64> {
+        match self {
+            Self::Pixel(..) | Self::Line(..) => None,
+            Self::Rect(_, width, height) => {
+                Some(Vector::new(*width as f64, *height as f64).length() / 2.0)
+            }
+            Self::Ellipse(_, a, b) => Some((*a).max(*b) as f64),
+            Self::Polygon(points) if points.len() > 2 => {
+                let center = self.center(&Vector::zero());
+                Some(
+                    points
+                        .iter()
+                        .map(|point| point.distance(&center))
+                        .fold(0.0, f64::max),
+                )
+            }
+            Self::Polygon(..) => None,
+        }
+    }
+
+    ///The point of the shape that lies furthest in `direction` in worldspace
+    pub(crate) fn support(&self, pos: &Vector, degree: f64, direction: &Vector) -> Vector {
+        let furthest = |points: &[Vector]| {
+            points
+                .iter()
+                .max_by(|p1, p2| (*p1 * direction).total_cmp(&(*p2 * direction)))
+                .unwrap()
+                .clone()
+        };
+        match self {
+            Self::Pixel(position) => pos + position,
+            Self::Line(start, end) => {
+                let (start, end) = CollisionShape::line_points(start, end, pos, degree);
+                furthest(&[start, end])
+            }
+            Self::Rect(center, width, height) => furthest(&CollisionShape::rect_corners(
+                center, *width, *height, pos, degree,
+            )),
+            Self::Ellipse(center, a, b) => {
+                let (a, b) = (*a as f64, *b as f64);
+                let local = direction.rotate_degree(-degree);
+                let scale = ((a * local.x).powi(2) + (b * local.y).powi(2)).sqrt();
+                if scale == 0.0 {
+                    return pos + center;
+                }
+                let point = Vector::new(a * a * local.x, b * b * local.y) / scale;
+                pos + center + point.rotate_degree(degree)
+            }
+            Self::Polygon(points) if points.is_empty() => pos.clone(),
+            Self::Polygon(points) => furthest(&Shape::polygon_points(points, pos, degree)),
+        }
+    }
+
+    ///Smallest axis aligned rectangle containing the shape in worldspace, given by its minimal and maximal corner
+    pub(crate) fn bounding_box(&self, pos: &Vector, degree: f64) -> (Vector, Vector) {
+        let x = Vector::new(1.0, 0.0);
+        let y = Vector::new(0.0, 1.0);
+        (
+            Vector::new(
+                self.support(pos, degree, &-&x).x,
+                self.support(pos, degree, &-&y).y,
+            ),
+            Vector::new(
+                self.support(pos, degree, &x).x,
+                self.support(pos, degree, &y).y,
+            ),
+        )
+    }
+
+    ///Calculates the `Contact` between `self` and `other`, if their outlines intersect. The normal points from `self` to `other`.
+    ///
+    ///The normal is perpendicular to the chord through the intersection points, which is the line the points spread along the most.
+    ///It is oriented by the smaller of the shapes with an area, which stays on the side of the chord its center lies on.
+    ///That way a shape inside a hollow one is pushed back inside.
+    ///The depth adds up how far each shape reaches over the chord, except for a shape enclosing the other one.
+    pub fn contact(
+        &self,
+        self_pos: &Vector,
+        self_degree: f64,
+        other: &CollisionShape,
+        other_pos: &Vector,
+        other_degree: f64,
+    ) -> Option<Contact> {
+        let points = self.intersection(self_pos, self_degree, other, other_pos, other_degree)?;
+        let center = self.center(self_pos);
+        let other_center = other.center(other_pos);
+
+        // true if other is the reference
+        let reference = match (self.area_radius(), other.area_radius()) {
+            (Some(radius), Some(other_radius)) => Some(radius >= other_radius),
+            (None, Some(_)) => Some(true),
+            (Some(_), None) => Some(false),
+            (None, None) => None,
+        };
+        let mut fallback = (&other_center - &center).normalize();
+        if fallback == Vector::zero() {
+            fallback = Vector::new(1.0, 0.0);
+        }
+
+        let (mut normal, chord_center) = if points.len() < 2 {
+            let normal = match reference {
+                Some(true) => (&other_center - &points[0]).normalize(),
+                Some(false) => (&points[0] - &center).normalize(),
+                None => Vector::zero(),
+            };
+            (normal, points[0].clone())
+        } else {
+            let chord_center =
+                points.iter().fold(Vector::zero(), |sum, point| sum + point) / points.len() as f64;
+            let (mut xx, mut yy, mut xy) = (0.0, 0.0, 0.0);
+            for point in &points {
+                let offset = point - &chord_center;
+                xx += offset.x * offset.x;
+                yy += offset.y * offset.y;
+                xy += offset.x * offset.y;
+            }
+            let chord_angle = 0.5 * (2.0 * xy).atan2(xx - yy);
+            (
+                Vector::new(-chord_angle.sin(), chord_angle.cos()),
+                chord_center,
+            )
+        };
+        if normal == Vector::zero() {
+            normal = fallback.clone();
+        }
+        let side = match reference {
+            Some(true) => (&other_center - &chord_center) * &normal,
+            Some(false) => (&chord_center - &center) * &normal,
+            None => &fallback * &normal,
+        };
+        if side < 0.0 {
+            normal = -normal;
+        }
+
+        let self_depth = if (&center - &chord_center) * &normal > 0.0 {
+            0.0
+        } else {
+            (self.support(self_pos, self_degree, &normal) - &chord_center) * &normal
+        };
+        let other_depth = if (&other_center - &chord_center) * &normal < 0.0 {
+            0.0
+        } else {
+            (&chord_center - other.support(other_pos, other_degree, &-&normal)) * &normal
+        };
+
+        Some(Contact {
+            depth: (self_depth + other_depth).max(0.0),
+            normal,
+            points,
+        })
+    }
+
+    ///Calculates the `Contact` between `self` and `other` treating both shapes as filled. The normal points from `self` to `other`.
+    ///
+    ///A shape inside the other one overlaps it, so the normal and depth describe the shortest way to push them apart.
+    ///The points are the intersections of the outlines or, if one shape contains the other, the deepest point of `other`.
+    ///Shapes that only touch are handled like hollow ones.
+    pub fn solid_contact(
+        &self,
+        self_pos: &Vector,
+        self_degree: f64,
+        other: &CollisionShape,
+        other_pos: &Vector,
+        other_degree: f64,
+    ) -> Option<Contact> {
+        let support = |direction: &Vector| {
+            self.support(self_pos, self_degree, direction)
+                - other.support(other_pos, other_degree, &-direction)
+        };
+        let direction = self.center(self_pos) - other.center(other_pos);
+        let simplex = match CollisionShape::enclosing_simplex(&support, direction) {
+            Some(simplex) => simplex,
+            None => return self.contact(self_pos, self_degree, other, other_pos, other_degree),
+        };
+        let (normal, depth) = CollisionShape::penetration(&support, simplex);
+        let points = self
+            .intersection(self_pos, self_degree, other, other_pos, other_degree)
+            .unwrap_or_else(|| vec![other.support(other_pos, other_degree, &-&normal)]);
+        Some(Contact {
+            normal,
+            depth,
+            points,
+        })
+    }
+
+    ///Searches a triangle inside the Minkowski difference described by `support`, that encloses the origin (GJK).
+    ///
+    ///Returns `None`, if the origin lies outside of the difference or on its border, so the shapes do not overlap.
+    fn enclosing_simplex(
+        support: &impl Fn(&Vector) -> Vector,
+        start_direction: Vector,
+    ) -> Option<[Vector; 3]> {
+        let mut direction = if start_direction == Vector::zero() {
+            Vector::new(1.0, 0.0)
+        } else {
+            start_direction
+        };
+        let mut simplex = vec![support(&direction)];
+        direction = -&simplex[0];
+        for _ in 0..64 {
+            if direction == Vector::zero() {
+                return None;
+            }
+            let newest = support(&direction);
+            if &newest * &direction <= 0.0 {
+                return None;
+            }
+            simplex.push(newest);
+            let a = simplex.last().unwrap().clone();
+            let to_origin = -&a;
+            if simplex.len() == 2 {
+                let ab = &simplex[0] - &a;
+                direction = ab.perpendicular();
+                if &direction * &to_origin < 0.0 {
+                    direction = -direction;
+                }
+                continue;
+            }
+            let ab = &simplex[1] - &a;
+            let ac = &simplex[0] - &a;
+            let mut ab_normal = ab.perpendicular();
+            if &ab_normal * &ac > 0.0 {
+                ab_normal = -ab_normal;
+            }
+            let mut ac_normal = ac.perpendicular();
+            if &ac_normal * &ab > 0.0 {
+                ac_normal = -ac_normal;
+            }
+            if &ab_normal * &to_origin > 0.0 {
+                simplex.remove(0);
+                direction = ab_normal;
+            } else if &ac_normal * &to_origin > 0.0 {
+                simplex.remove(1);
+                direction = ac_normal;
+            } else {
+                return Some([simplex[0].clone(), simplex[1].clone(), a]);
+            }
+        }
+        None
+    }
+
+    ///Expands the `simplex` to the border of the Minkowski difference described by `support`, until the closest edge to the origin is found (EPA).
+    ///
+    ///Returns the unit normal of that edge and its distance to the origin.
+    fn penetration(support: &impl Fn(&Vector) -> Vector, simplex: [Vector; 3]) -> (Vector, f64) {
+        let mut polytope = simplex.to_vec();
+        if (&polytope[1] - &polytope[0]).cross(&(&polytope[2] - &polytope[0])) < 0.0 {
+            polytope.swap(1, 2);
+        }
+        let mut closest = (Vector::zero(), f64::INFINITY);
+        for _ in 0..128 {
+            closest = (Vector::zero(), f64::INFINITY);
+            let mut closest_index = 0;
+            for i in 0..polytope.len() {
+                let edge = &polytope[(i + 1) % polytope.len()] - &polytope[i];
+                let normal = Vector::new(edge.y, -edge.x).normalize();
+                let distance = &normal * &polytope[i];
+                if distance < closest.1 {
+                    closest = (normal, distance);
+                    closest_index = i;
+                }
+            }
+            let point = support(&closest.0);
+            if &point * &closest.0 - closest.1 < 1e-9 {
+                break;
+            }
+            polytope.insert(closest_index + 1, point);
+        }
+        (closest.0, closest.1.max(0.0))
+    }
+
     fn intersection(
         &self,
         self_pos: &Vector,
@@ -555,6 +1118,14 @@ impl CollisionShape {
                     other_pos,
                     other_degree,
                 ),
+                Self::Polygon(..) => CollisionShape::intersect_polygon(
+                    other,
+                    other_pos,
+                    other_degree,
+                    self,
+                    self_pos,
+                    self_degree,
+                ),
             },
             Self::Pixel(..) => match other {
                 Self::Line(..) => CollisionShape::intersect_line_pixel(
@@ -581,6 +1152,14 @@ impl CollisionShape {
                     other_pos,
                     other_degree,
                 ),
+                Self::Polygon(..) => CollisionShape::intersect_polygon(
+                    other,
+                    other_pos,
+                    other_degree,
+                    self,
+                    self_pos,
+                    self_degree,
+                ),
             },
             Self::Rect(..) => match other {
                 Self::Line(..) => CollisionShape::intersect_line_rect(
@@ -614,9 +1193,7 @@ impl CollisionShape {
                     other_pos,
                     other_degree,
                 ),
-            },
-            Self::Ellipse(..) => match other {
-                Self::Line(..) => CollisionShape::intersect_line_ellipse(
+                Self::Polygon(..) => CollisionShape::intersect_polygon(
                     other,
                     other_pos,
                     other_degree,
@@ -624,9 +1201,19 @@ impl CollisionShape {
                     self_pos,
                     self_degree,
                 ),
-                Self::Pixel(..) => CollisionShape::intersect_pixel_ellipse(
-                    other,
-                    other_pos,
+            },
+            Self::Ellipse(..) => match other {
+                Self::Line(..) => CollisionShape::intersect_line_ellipse(
+                    other,
+                    other_pos,
+                    other_degree,
+                    self,
+                    self_pos,
+                    self_degree,
+                ),
+                Self::Pixel(..) => CollisionShape::intersect_pixel_ellipse(
+                    other,
+                    other_pos,
                     self,
                     self_pos,
                     self_degree,
@@ -647,21 +1234,381 @@ impl CollisionShape {
                     other_pos,
                     other_degree,
                 ),
+                Self::Polygon(..) => CollisionShape::intersect_polygon(
+                    other,
+                    other_pos,
+                    other_degree,
+                    self,
+                    self_pos,
+                    self_degree,
+                ),
             },
+            Self::Polygon(..) => CollisionShape::intersect_polygon(
+                self,
+                self_pos,
+                self_degree,
+                other,
+                other_pos,
+                other_degree,
+            ),
         };
 
-        if intersection_points.len() == 0 {
+        let mut unique_points: Vec<Vector> = vec![];
+        for point in intersection_points {
+            if unique_points
+                .iter()
+                .all(|unique| unique.distance(&point) > 1e-9)
+            {
+                unique_points.push(point);
+            }
+        }
+
+        if unique_points.is_empty() {
             None
         } else {
-            Some(intersection_points)
+            Some(unique_points)
+        }
+    }
+}
+
+/// Describes where a ray hit a body. A `CollisionLayer` reports the hit `body` as `BodyHandle`
+#[derive(PartialEq, Debug, Clone)]
+pub struct RaycastHit<B = RefBody> {
+    pub body: B,
+    /// Point where the ray hit the outline of the body in worldspace
+    pub point: Vector,
+    /// Unit normal of the outline at `point`, facing the origin of the ray
+    pub normal: Vector,
+    /// Distance from the origin of the ray to `point`
+    pub distance: f64,
+}
+
+/// Describes how two colliding shapes touch each other
+#[derive(PartialEq, Debug, Clone)]
+pub struct Contact {
+    /// Unit vector pointing from the first to the second shape. Moving the second shape along it separates them
+    pub normal: Vector,
+    /// Distance the shapes have to be moved apart along the `normal` to stop overlapping
+    pub depth: f64,
+    /// Points where the outlines of the shapes intersect in worldspace
+    pub points: Vec<Vector>,
+}
+impl Contact {
+    ///The outermost `points` along the contact, at which the impulses of a collision act. Empty if there are no `points`
+    fn support_points(&self) -> Vec<Vector> {
+        let tangent = self.normal.perpendicular();
+        let along = |point: &&Vector| *point * &tangent;
+        let first = self
+            .points
+            .iter()
+            .min_by(|a, b| along(a).total_cmp(&along(b)));
+        let last = self
+            .points
+            .iter()
+            .max_by(|a, b| along(a).total_cmp(&along(b)));
+        match (first, last) {
+            (Some(first), Some(last)) if first.distance(last) > 1e-9 => {
+                vec![first.clone(), last.clone()]
+            }
+            (Some(first), _) => vec![first.clone()],
+            _ => vec![],
+        }
+    }
+}
+
+///Solves the normal impulses of a contact at two points, so that both stop approaching each other at the `targets` speeds without pulling the bodies together.
+///
+///`response[i][j]` is the change of the approach speed at point `i` caused by a unit impulse at point `j`.
+fn normal_impulses(response: [[f64; 2]; 2], targets: [f64; 2]) -> [f64; 2] {
+    let determinant = response[0][0] * response[1][1] - response[0][1] * response[1][0];
+    if determinant > 1e-12 * response[0][0] * response[1][1] {
+        let first = (response[1][1] * targets[0] - response[0][1] * targets[1]) / determinant;
+        let second = (response[0][0] * targets[1] - response[1][0] * targets[0]) / determinant;
+        if first >= 0.0 && second >= 0.0 {
+            return [first, second];
         }
     }
+    //Otherwise only one of the points is pushed
+    if response[0][0] > 0.0 {
+        let first = targets[0] / response[0][0];
+        if first >= 0.0 && targets[1] - response[1][0] * first <= 0.0 {
+            return [first, 0.0];
+        }
+    }
+    if response[1][1] > 0.0 {
+        let second = targets[1] / response[1][1];
+        if second >= 0.0 && targets[0] - response[0][1] * second <= 0.0 {
+            return [0.0, second];
+        }
+    }
+    [0.0, 0.0]
 }
 
+///The part of a `Body` that takes part in collisions.
+///
+///By default the `shape` is hollow, so only its outline collides and smaller bodies can move around inside of it like in an arena.
+///A solid `CollisionBody` is filled instead and pushes out everything overlapping it.
+///
+///A sensor only detects the bodies it touches inside its `CollisionLayer`, without ever changing their or its own movement.
+///
+///How bouncy and slippery the body is, is set by its `Material`.
 #[derive(PartialEq, Debug, Clone)]
 pub struct CollisionBody {
     shape: CollisionShape,
     behaviour: Mass,
+    solid: bool,
+    sensor: bool,
+    material: Material,
+}
+impl CollisionBody {
+    ///Creates a hollow `CollisionBody`
+    pub fn new(shape: Shape, behaviour: Mass) -> Self {
+        CollisionBody {
+            shape,
+            behaviour,
+            solid: false,
+            sensor: false,
+            material: Material::default(),
+        }
+    }
+
+    ///Creates a solid `CollisionBody`
+    pub fn new_solid(shape: Shape, behaviour: Mass) -> Self {
+        CollisionBody {
+            shape,
+            behaviour,
+            solid: true,
+            sensor: false,
+            material: Material::default(),
+        }
+    }
+
+    pub fn shape(&self) -> &Shape {
+        &self.shape
+    }
+
+    pub fn is_solid(&self) -> bool {
+        self.solid
+    }
+
+    ///Turns the `CollisionBody` into a sensor, that reports contacts but never responds to them. Usually combined with `new_solid` for zones and pickups
+    pub fn sensor(mut self) -> Self {
+        self.sensor = true;
+        self
+    }
+
+    pub fn is_sensor(&self) -> bool {
+        self.sensor
+    }
+
+    ///Sets the `Material` used when responding to collisions. Defaults to `Material::default`
+    pub fn material(mut self, material: Material) -> Self {
+        self.material = material;
+        self
+    }
+
+    pub fn get_material(&self) -> &Material {
+        &self.material
+    }
+
+    ///Calculates the `Contact` with `other`. The normal points from `self` to `other`.
+    ///
+    ///Whether the shapes are treated as hollow or solid is decided by the bigger one, as it is the one that could contain the other.
+    pub fn contact(
+        &self,
+        position: &Vector,
+        degree: f64,
+        other: &CollisionBody,
+        other_position: &Vector,
+        other_degree: f64,
+    ) -> Option<Contact> {
+        let solid = match (self.shape.area_radius(), other.shape.area_radius()) {
+            (Some(radius), Some(other_radius)) => {
+                if radius >= other_radius {
+                    self.solid
+                } else {
+                    other.solid
+                }
+            }
+            (Some(_), None) => self.solid,
+            (None, Some(_)) => other.solid,
+            (None, None) => false,
+        };
+        if solid {
+            self.shape
+                .solid_contact(position, degree, &other.shape, other_position, other_degree)
+        } else {
+            self.shape
+                .contact(position, degree, &other.shape, other_position, other_degree)
+        }
+    }
+
+    ///Checks `body` and `other` for a collision and exchanges their impulses along the contact normal according to their `Mass`.
+    ///The impulses act at the outermost contact points, so off-center hits also change the `rotation_velocity` of the bodies.
+    ///The exchanged impulses are scaled by the combined restitution of their `Material`s and friction slows down their sliding along the contact.
+    ///Afterwards the bodies are pushed apart by the penetration depth, where lighter bodies are moved further.
+    ///
+    ///Bodies that are already moving apart keep their velocities and sensors never respond. Bodies, that are not `Dynamic`, act with `Mass::Infinite` and never collide with each other, unless one of them is a sensor.
+    ///Returns the `Contact` if the bodies collide.
+    pub(crate) fn collide(body: &mut Body<Vector>, other: &mut Body<Vector>) -> Option<Contact> {
+        let behaviour = |body: &Body<Vector>, collision_body: &CollisionBody| {
+            if body.is_dynamic() {
+                collision_body.behaviour.clone()
+            } else {
+                Mass::Infinite
+            }
+        };
+        let (contact, mass, other_mass, material, unit_inertias) =
+            match (body.collision_body(), other.collision_body()) {
+                (Some(collision_body), Some(other_collision_body)) => {
+                    let sensor = collision_body.sensor || other_collision_body.sensor;
+                    if !sensor && !body.is_dynamic() && !other.is_dynamic() {
+                        return None;
+                    }
+                    let contact = collision_body.contact(
+                        &body.position,
+                        body.angle_deg,
+                        other_collision_body,
+                        &other.position,
+                        other.angle_deg,
+                    )?;
+                    if sensor {
+                        return Some(contact);
+                    }
+                    (
+                        contact,
+                        behaviour(body, collision_body),
+                        behaviour(other, other_collision_body),
+                        collision_body
+                            .material
+                            .combine(&other_collision_body.material),
+                        (
+                            collision_body.shape.moment_of_inertia(1.0),
+                            other_collision_body.shape.moment_of_inertia(1.0),
+                        ),
+                    )
+                }
+                _ => return None,
+            };
+        let normal = &contact.normal;
+        let tangent = normal.perpendicular();
+        let (inverse_mass, other_inverse_mass) = mass.inverse_values(&other_mass);
+        //The moments of inertia scale with the masses used in the collision
+        let inverse_inertia = |unit_inertia: f64, inverse_mass: f64| {
+            if unit_inertia > 0.0 {
+                inverse_mass / unit_inertia
+            } else {
+                0.0
+            }
+        };
+        let (inverse_inertia, other_inverse_inertia) = (
+            inverse_inertia(unit_inertias.0, inverse_mass),
+            inverse_inertia(unit_inertias.1, other_inverse_mass),
+        );
+
+        //Levers from the centers of mass of both bodies to the points the impulses act at. Without points they act at the centers
+        let mut levers: Vec<(Vector, Vector)> = contact
+            .support_points()
+            .into_iter()
+            .map(|point| {
+                (
+                    &point - body.center_of_mass(),
+                    &point - other.center_of_mass(),
+                )
+            })
+            .collect();
+        if levers.is_empty() {
+            levers.push((Vector::zero(), Vector::zero()));
+        }
+        let point_velocity = |body: &Body<Vector>, lever: &Vector| {
+            &body.velocity + lever.perpendicular() * body.rotation_velocity.to_radians()
+        };
+        let relative_velocity =
+            |body: &Body<Vector>, other: &Body<Vector>, (lever, other_lever): &(Vector, Vector)| {
+                point_velocity(body, lever) - point_velocity(other, other_lever)
+            };
+        //How much an impulse along `direction` at `levers` changes the relative velocity along `direction` at `other_levers`
+        let response =
+            |levers: &(Vector, Vector), other_levers: &(Vector, Vector), direction: &Vector| {
+                inverse_mass
+                    + other_inverse_mass
+                    + levers.0.cross(direction) * other_levers.0.cross(direction) * inverse_inertia
+                    + levers.1.cross(direction)
+                        * other_levers.1.cross(direction)
+                        * other_inverse_inertia
+            };
+        //Pushes `body` by `-impulse` and `other` by `impulse` at `levers`
+        let exchange = |body: &mut Body<Vector>,
+                        other: &mut Body<Vector>,
+                        (lever, other_lever): &(Vector, Vector),
+                        impulse: &Vector| {
+            body.velocity -= impulse * inverse_mass;
+            body.rotation_velocity -= (lever.cross(impulse) * inverse_inertia).to_degrees();
+            other.velocity += impulse * other_inverse_mass;
+            other.rotation_velocity +=
+                (other_lever.cross(impulse) * other_inverse_inertia).to_degrees();
+        };
+
+        let approaches: Vec<f64> = levers
+            .iter()
+            .map(|levers| relative_velocity(body, other, levers) * normal)
+            .collect();
+        if approaches.iter().any(|approach| *approach > 0.0) {
+            let targets: Vec<f64> = approaches
+                .iter()
+                .map(|approach| approach + material.restitution * approach.max(0.0))
+                .collect();
+            let impulses = match levers.as_slice() {
+                [first, second] => normal_impulses(
+                    [
+                        [
+                            response(first, first, normal),
+                            response(first, second, normal),
+                        ],
+                        [
+                            response(second, first, normal),
+                            response(second, second, normal),
+                        ],
+                    ],
+                    [targets[0], targets[1]],
+                )
+                .to_vec(),
+                _ => {
+                    let response = response(&levers[0], &levers[0], normal);
+                    vec![if response > 0.0 {
+                        (targets[0] / response).max(0.0)
+                    } else {
+                        0.0
+                    }]
+                }
+            };
+            for (levers, impulse) in levers.iter().zip(&impulses) {
+                exchange(body, other, levers, &(normal * *impulse));
+            }
+
+            for (levers, impulse) in levers.iter().zip(&impulses) {
+                let sliding = relative_velocity(body, other, levers) * &tangent;
+                let response = response(levers, levers, &tangent);
+                if sliding == 0.0 || response <= 0.0 {
+                    continue;
+                }
+                let stopping_impulse = sliding / response;
+                let friction_impulse =
+                    if stopping_impulse.abs() <= material.static_friction * impulse {
+                        stopping_impulse
+                    } else {
+                        sliding.signum()
+                            * (material.dynamic_friction * impulse).min(stopping_impulse.abs())
+                    };
+                exchange(body, other, levers, &(&tangent * friction_impulse));
+            }
+        }
+
+        let (share, other_share) = mass.separation_shares(&other_mass);
+        body.position -= (contact.depth * share) * normal;
+        other.position += (contact.depth * other_share) * normal;
+        Some(contact)
+    }
 }
 
 /// Variants describing the behaviour of a `CollisionBody`, when colliding with something
@@ -669,12 +1616,54 @@ pub struct CollisionBody {
 /// * `Infinite`: Acts as a wall with infinite mass. Basically absorbs all of the impacting bodies impulse and reflects double it (p2' = - p2)
 /// * `Copy`: Copies the impacting bodies mass. Results in a simple impulse transfer between them (p1' = p2; p2' = p1)
 /// * `Elastic(masss: f64)`: a finite mass of a elastically colliding body.
+///
+/// The formulas describe perfectly elastic collisions. The restitution of the `Material`s scales down the exchanged impulses.
 #[derive(PartialEq, Debug, Clone)]
 pub enum Mass {
     Infinite,
     Copy,
     Elastic(f64),
 }
+impl Mass {
+    ///The mass used in a collision with a body of `other` mass. `None` represents an infinite mass
+    fn value(&self, other: &Mass) -> Option<f64> {
+        match self {
+            Mass::Infinite => None,
+            Mass::Elastic(mass) => Some(*mass),
+            Mass::Copy => match other {
+                Mass::Elastic(mass) => Some(*mass),
+                _ => Some(1.0),
+            },
+        }
+    }
+
+    ///Inverse masses of two colliding bodies, that scale the impulses they receive. An infinite mass has an inverse of zero
+    fn inverse_values(&self, other: &Mass) -> (f64, f64) {
+        let inverse =
+            |mass: Option<f64>| mass.map_or(0.0, |mass| 1.0 / mass.max(f64::MIN_POSITIVE));
+        match (self.value(other), other.value(self)) {
+            (Some(mass), Some(other_mass)) if mass + other_mass <= 0.0 => (1.0, 1.0),
+            (mass, other_mass) => (inverse(mass), inverse(other_mass)),
+        }
+    }
+
+    ///The parts of the penetration depth each of two colliding bodies is moved to separate them. The lighter body moves further
+    fn separation_shares(&self, other: &Mass) -> (f64, f64) {
+        match (self.value(other), other.value(self)) {
+            (None, None) => (0.0, 0.0),
+            (None, Some(_)) => (0.0, 1.0),
+            (Some(_), None) => (1.0, 0.0),
+            (Some(mass), Some(other_mass)) => {
+                if mass + other_mass > 0.0 {
+                    let total = mass + other_mass;
+                    (other_mass / total, mass / total)
+                } else {
+                    (0.5, 0.5)
+                }
+            }
+        }
+    }
+}
 
 /// An area in which collidable objects are grouped together.
 ///
@@ -684,11 +1673,16 @@ pub enum Mass {
 ///
 #[derive(Debug)]
 pub struct CollisionLayer {
-    obstacles: HashSet<RefBody>,
-    actors: HashSet<RefBody>,
-    collision_grid: SpatialHashgrid<Uuid>,
+    obstacles: HashSet<BodyHandle>,
+    actors: HashSet<BodyHandle>,
+    collision_grid: SpatialHashgrid<BodyHandle>,
+    ///Bounding box of every body in the layer, as it is inserted into the `collision_grid`
+    bounds: HashMap<BodyHandle, (Vector, Vector)>,
 }
-///TODO: WHAT IS WITH BIG BODY SPANNING OVER MULTIPLE CELLS?
+///Bodies are inserted into every cell of the `SpatialHashgrid` their bounding box covers.
+///Collisions are only checked between bodies sharing a cell or lying in neighbouring cells.
+///
+///The layer only stores `BodyHandle`s. The bodies themselves live in a `BodySet`, that is passed to every method needing them.
 impl CollisionLayer {
     ///Creates a new CollisionLayer. The underlaying SpatialHashgrid will have the total dimensions `grid_size` and each cell in the grid has the dimensions `cell_size`
     pub fn new(grid_size: PhysicalSize<u32>, cell_size: PhysicalSize<u32>) -> Self {
@@ -696,74 +1690,1101 @@ impl CollisionLayer {
             obstacles: HashSet::new(),
             actors: HashSet::new(),
             collision_grid: SpatialHashgrid::new(grid_size, cell_size),
+            bounds: HashMap::new(),
         }
     }
 
-    //Adds the `collision_body` to the layer according to `is_obstacle`.
+    ///Bounding box of the `CollisionBody` of `body`. Bodies without one are treated as a point at their position
+    fn bounding_box(body: &Body<Vector>) -> (Vector, Vector) {
+        match body.collision_body() {
+            Some(collision_body) => collision_body
+                .shape
+                .bounding_box(&body.position, body.angle_deg),
+            None => (body.position.clone(), body.position.clone()),
+        }
+    }
+
+    ///Adds the body of `handle` in `bodies` to the layer according to `is_obstacle`.
     ///
     ///Returns whether the value was newly inserted. That is:
     ///
     ///If the underliying `SpatialHashgrid` did not previously contain this value, true is returned. If the grid already contained this value, false is returned and the body does not get added again.
     /// Meaning: If a body is allready part of the layer as a actor, it can not be added as a obstacle, before being removed and vice versa.
-    pub fn add_body(&mut self, collision_body: RefBody, is_obstacle: bool) -> bool {
-        let position = collision_body.position().clone();
-        if self.collision_grid.insert(collision_body.id(), &position) {
-            if is_obstacle {
-                self.obstacles.insert(collision_body)
-            } else {
-                self.actors.insert(collision_body)
-            };
-            true
-        } else {
-            false
+    ///Invalid handles are not added either.
+    pub fn add_body(&mut self, handle: BodyHandle, bodies: &BodySet, is_obstacle: bool) -> bool {
+        let body = match bodies.get(handle) {
+            Some(body) => body,
+            None => return false,
+        };
+        if self.bounds.contains_key(&handle) {
+            return false;
         }
+        let (min, max) = CollisionLayer::bounding_box(body);
+        self.collision_grid.insert_area(handle, &min, &max);
+        self.bounds.insert(handle, (min, max));
+        if is_obstacle {
+            self.obstacles.insert(handle)
+        } else {
+            self.actors.insert(handle)
+        };
+        true
     }
 
     ///Removes the body from the collision layer, if it is contained
     ///
     ///Return `true` if the body was part of the layer
-    pub fn remove_body(&mut self, collision_body: &RefBody, is_obstacle: Option<bool>) -> bool {
-        if self
-            .collision_grid
-            .contains(&collision_body.id(), &collision_body.position())
-        {
+    pub fn remove_body(&mut self, handle: BodyHandle, is_obstacle: Option<bool>) -> bool {
+        if self.bounds.contains_key(&handle) {
             let body_in_layer = match is_obstacle {
                 Some(is_obstacle) => {
                     if is_obstacle {
-                        self.obstacles.remove(collision_body)
+                        self.obstacles.remove(&handle)
                     } else {
-                        self.actors.remove(collision_body)
+                        self.actors.remove(&handle)
                     }
                 }
                 None => {
-                    if !self.obstacles.remove(collision_body) {
-                        self.actors.remove(collision_body)
+                    if !self.obstacles.remove(&handle) {
+                        self.actors.remove(&handle)
                     } else {
                         true
                     }
                 }
             };
             if body_in_layer {
-                self.collision_grid
-                    .remove(&collision_body.id(), &collision_body.position());
+                let (min, max) = self.bounds.remove(&handle).unwrap();
+                self.collision_grid.remove_area(&handle, &min, &max);
             }
             body_in_layer
         } else {
             false
         }
     }
+
+    ///Pairs of bodies in the layer, that may be touching, because they share a cell of the underlying `SpatialHashgrid` or lie in neighbouring cells.
+    ///
+    ///Contains every pair of `obstacles` and every pair of an obstacle with an actor exactly once, with the obstacle first. `actors` are never paired with each other.
+    ///Only the neighbourhoods of bodies, that are awake in `bodies`, are searched, so pairs of sleeping, `Static` or resting `Kinematic` bodies are left out.
+    ///The pairs are sorted by their handles, so they are resolved in the same order every `step`.
+    pub fn candidate_pairs(
+        &self,
+        bodies: &BodySet,
+    ) -> impl Iterator<Item = (BodyHandle, BodyHandle)> {
+        let mut pairs = vec![];
+        for body in self.awake_bodies(bodies) {
+            let is_obstacle = self.obstacles.contains(body);
+            let (min, max) = &self.bounds[body];
+            for other in self.collision_grid.get_neighbourhood(min, max) {
+                if other == body {
+                    continue;
+                }
+                match (is_obstacle, self.obstacles.contains(other)) {
+                    (true, true) => pairs.push((*body.min(other), *body.max(other))),
+                    (true, false) if self.actors.contains(other) => pairs.push((*body, *other)),
+                    (false, true) => pairs.push((*other, *body)),
+                    _ => {}
+                }
+            }
+        }
+        pairs.sort();
+        pairs.dedup();
+        pairs.into_iter()
+    }
+
+    ///The bodies of the layer, that are awake in `bodies`
+    fn awake_bodies<'a>(&'a self, bodies: &'a BodySet) -> impl Iterator<Item = &'a BodyHandle> {
+        self.obstacles
+            .iter()
+            .chain(self.actors.iter())
+            .filter(|handle| bodies.get(**handle).is_some_and(|body| body.is_awake()))
+    }
+
+    ///Lets all bodies in the layer, that touch each other, collide.
+    ///
+    ///`obstacles` collide with each other and with `actors`. `actors` only collide with `obstacles`.
+    ///Bodies pushed apart by a collision are moved inside the underlying `SpatialHashgrid` as well.
+    ///Pairs of bodies, that are both sleeping, `Static` or resting `Kinematic` ones, are skipped. Sleeping bodies touched by a moving one are woken up.
+    ///
+    ///Returns the handles of every colliding pair together with their `Contact`
+    pub fn resolve_collisions(
+        &mut self,
+        bodies: &mut BodySet,
+    ) -> Vec<(BodyHandle, BodyHandle, Contact)> {
+        let contacts = CollisionLayer::collide_pairs(self.candidate_pairs(bodies), bodies);
+        for (body, other, _) in &contacts {
+            self.update_body(*body, bodies);
+            self.update_body(*other, bodies);
+        }
+        contacts
+    }
+
+    ///Pairs of bodies from `self` and `other`, that may be touching, with the body of `self` first.
+    ///
+    ///Across layers every body can collide with every other body, regardless of being an obstacle or an actor. Pairs and sorting work like in `candidate_pairs`.
+    pub fn candidate_pairs_with(
+        &self,
+        other: &CollisionLayer,
+        bodies: &BodySet,
+    ) -> impl Iterator<Item = (BodyHandle, BodyHandle)> {
+        let mut pairs = vec![];
+        for body in self.awake_bodies(bodies) {
+            let (min, max) = &self.bounds[body];
+            for other_body in other.collision_grid.get_neighbourhood(min, max) {
+                if other.bounds.contains_key(other_body) {
+                    pairs.push((*body, *other_body));
+                }
+            }
+        }
+        for other_body in other.awake_bodies(bodies) {
+            let (min, max) = &other.bounds[other_body];
+            for body in self.collision_grid.get_neighbourhood(min, max) {
+                if self.bounds.contains_key(body) {
+                    pairs.push((*body, *other_body));
+                }
+            }
+        }
+        pairs.sort();
+        pairs.dedup();
+        pairs.into_iter()
+    }
+
+    ///Lets all bodies of `self` collide with the ones of `other`, that touch them. Just like `resolve_collisions`, but between two layers.
+    pub fn resolve_collisions_with(
+        &mut self,
+        other: &mut CollisionLayer,
+        bodies: &mut BodySet,
+    ) -> Vec<(BodyHandle, BodyHandle, Contact)> {
+        let contacts =
+            CollisionLayer::collide_pairs(self.candidate_pairs_with(other, bodies), bodies);
+        for (body, other_body, _) in &contacts {
+            self.update_body(*body, bodies);
+            other.update_body(*other_body, bodies);
+        }
+        contacts
+    }
+
+    fn collide_pairs(
+        pairs: impl Iterator<Item = (BodyHandle, BodyHandle)>,
+        bodies: &mut BodySet,
+    ) -> Vec<(BodyHandle, BodyHandle, Contact)> {
+        let mut contacts = vec![];
+        for (handle, other_handle) in pairs {
+            if let Some((body, other)) = bodies.get_pair_mut(handle, other_handle) {
+                if let Some(contact) = CollisionBody::collide(body, other) {
+                    let is_sensor = |body: &Body<Vector>| {
+                        body.collision_body()
+                            .is_some_and(|collision_body| collision_body.sensor)
+                    };
+                    if !is_sensor(body) && !is_sensor(other) {
+                        body.wake_up();
+                        other.wake_up();
+                    }
+                    contacts.push((handle, other_handle, contact));
+                }
+            }
+        }
+        contacts
+    }
+
+    ///Casts a ray from `origin` along `direction` for at most `max_distance` against the bodies in the layer.
+    ///
+    ///Only bodies in the cells of the underlying `SpatialHashgrid` around the ray are tested.
+    ///Returns every hit, sorted by their distance to `origin`.
+    pub fn raycast(
+        &self,
+        bodies: &BodySet,
+        origin: &Vector,
+        direction: &Vector,
+        max_distance: f64,
+    ) -> Vec<RaycastHit<BodyHandle>> {
+        let direction = direction.normalize();
+        if direction == Vector::zero() {
+            return vec![];
+        }
+        let end = origin + &direction * max_distance;
+        let min = Vector::new(origin.x.min(end.x), origin.y.min(end.y));
+        let max = Vector::new(origin.x.max(end.x), origin.y.max(end.y));
+        let mut hits: Vec<RaycastHit<BodyHandle>> = self
+            .collision_grid
+            .get_neighbourhood(&min, &max)
+            .into_iter()
+            .filter(|handle| self.bounds.contains_key(handle))
+            .filter_map(|handle| {
+                let body = bodies.get(*handle)?;
+                let (point, normal, distance) = body.collision_body()?.shape.raycast(
+                    &body.position,
+                    body.angle_deg,
+                    origin,
+                    &direction,
+                    max_distance,
+                )?;
+                Some(RaycastHit {
+                    body: *handle,
+                    point,
+                    normal,
+                    distance,
+                })
+            })
+            .collect();
+        hits.sort_by(|hit, other| hit.distance.total_cmp(&other.distance));
+        hits
+    }
+
+    ///Returns the bodies in the layer, whose `CollisionBody` overlaps the filled `shape` at `position` turned by `angle_deg`.
+    ///
+    ///Only bodies in the cells of the underlying `SpatialHashgrid` around the bounding box of `shape` are tested.
+    pub fn query_shape(
+        &self,
+        bodies: &BodySet,
+        shape: &Shape,
+        position: &Vector,
+        angle_deg: f64,
+    ) -> Vec<BodyHandle> {
+        let (min, max) = shape.bounding_box(position, angle_deg);
+        self.collision_grid
+            .get_neighbourhood(&min, &max)
+            .into_iter()
+            .filter(|handle| self.bounds.contains_key(handle))
+            .filter(|handle| {
+                bodies
+                    .get(**handle)
+                    .and_then(|body| Some((body, body.collision_body()?)))
+                    .is_some_and(|(body, collision_body)| {
+                        collision_body.shape.overlaps(
+                            &body.position,
+                            body.angle_deg,
+                            shape,
+                            position,
+                            angle_deg,
+                        )
+                    })
+            })
+            .copied()
+            .collect()
+    }
+
+    ///Moves the body inside the underlying `SpatialHashgrid` to the cells covered by its current bounding box.
+    ///
+    ///Returns `false` if the body is not part of the layer or not in `bodies`
+    pub fn update_body(&mut self, handle: BodyHandle, bodies: &BodySet) -> bool {
+        let body = match bodies.get(handle) {
+            Some(body) => body,
+            None => return false,
+        };
+        let bounds = CollisionLayer::bounding_box(body);
+        match self.bounds.get_mut(&handle) {
+            Some(previous_bounds) => {
+                if *previous_bounds != bounds {
+                    self.collision_grid.remove_area(
+                        &handle,
+                        &previous_bounds.0,
+                        &previous_bounds.1,
+                    );
+                    self.collision_grid
+                        .insert_area(handle, &bounds.0, &bounds.1);
+                    *previous_bounds = bounds;
+                }
+                true
+            }
+            None => false,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::math_2d::Vector;
+    use crate::physics_2d::{BodyBuilder, BodyKind};
 
     use super::*;
 
+    fn assert_close(left: &Vector, right: &Vector) {
+        assert!(left.distance(right) < 1e-9, "{} != {}", left, right);
+    }
+
+    fn ball(position: Vector, velocity: Vector, behaviour: Mass) -> Body<Vector> {
+        BodyBuilder::new()
+            .position(position)
+            .velocity(velocity)
+            .collision(CollisionBody::new(
+                Shape::Ellipse(Vector::zero(), 10, 10),
+                behaviour,
+            ))
+            .build()
+    }
+
+    fn wall(position: Vector) -> Body<Vector> {
+        BodyBuilder::new()
+            .position(position)
+            .collision(CollisionBody::new(
+                Shape::Line(Vector::new(0.0, -100.0), Vector::new(0.0, 100.0)),
+                Mass::Infinite,
+            ))
+            .build()
+    }
+
+    #[test]
+    fn intersection_in_worldspace() {
+        let vertical = Shape::Line(Vector::new(0.0, -10.0), Vector::new(0.0, 10.0));
+        let horizontal = Shape::Line(Vector::new(-10.0, 0.0), Vector::new(10.0, 0.0));
+        assert_eq!(
+            vertical.intersection(
+                &Vector::new(5.0, 5.0),
+                0.0,
+                &horizontal,
+                &Vector::zero(),
+                0.0
+            ),
+            Some(vec![Vector::new(5.0, 0.0)])
+        );
+        assert_eq!(
+            horizontal.intersection(
+                &Vector::zero(),
+                90.0,
+                &horizontal,
+                &Vector::new(0.0, 20.0),
+                0.0
+            ),
+            None
+        );
+
+        let circle = Shape::Ellipse(Vector::zero(), 10, 10);
+        assert_eq!(
+            horizontal.intersection(&Vector::new(0.0, 10.0), 0.0, &circle, &Vector::zero(), 0.0),
+            Some(vec![Vector::new(0.0, 10.0)])
+        );
+        let points = vertical
+            .intersection(
+                &Vector::new(30.0, 0.0),
+                0.0,
+                &circle,
+                &Vector::new(30.0, 5.0),
+                0.0,
+            )
+            .unwrap();
+        assert_eq!(points.len(), 1);
+        assert!(points[0].distance(&Vector::new(30.0, -5.0)) < 1e-9);
+
+        let square = Shape::Rect(Vector::zero(), 20, 20);
+        assert_eq!(
+            square.intersection(&Vector::zero(), 0.0, &square, &Vector::new(10.0, 10.0), 0.0),
+            Some(vec![Vector::new(10.0, 0.0), Vector::new(0.0, 10.0)])
+        );
+        assert_eq!(
+            square.intersection(
+                &Vector::zero(),
+                0.0,
+                &Shape::Rect(Vector::zero(), 10, 10),
+                &Vector::zero(),
+                0.0
+            ),
+            None
+        );
+        assert_eq!(
+            Shape::Pixel(Vector::new(10.0, 5.0)).intersection(
+                &Vector::zero(),
+                0.0,
+                &square,
+                &Vector::zero(),
+                0.0
+            ),
+            Some(vec![Vector::new(10.0, 5.0)])
+        );
+    }
+
+    #[test]
+    fn intersect_ellipses() {
+        let circle = Shape::Ellipse(Vector::zero(), 10, 10);
+        let small_circle = Shape::Ellipse(Vector::zero(), 5, 5);
+        let ellipse = Shape::Ellipse(Vector::zero(), 20, 10);
+
+        // crossing
+        let points = ellipse
+            .intersection(&Vector::zero(), 0.0, &ellipse, &Vector::zero(), 90.0)
+            .unwrap();
+        let x = 80.0_f64.sqrt();
+        assert_eq!(points.len(), 4);
+        for expected in [
+            Vector::new(x, x),
+            Vector::new(-x, x),
+            Vector::new(-x, -x),
+            Vector::new(x, -x),
+        ] {
+            assert!(points.iter().any(|point| point.distance(&expected) < 1e-6));
+        }
+
+        // outer and inner tangent
+        let points = circle
+            .intersection(&Vector::zero(), 0.0, &circle, &Vector::new(20.0, 0.0), 0.0)
+            .unwrap();
+        assert_eq!(points.len(), 1);
+        assert!(points[0].distance(&Vector::new(10.0, 0.0)) < 1e-6);
+        let points = circle
+            .intersection(
+                &Vector::zero(),
+                0.0,
+                &small_circle,
+                &Vector::new(0.0, -5.0),
+                0.0,
+            )
+            .unwrap();
+        assert_eq!(points.len(), 1);
+        assert!(points[0].distance(&Vector::new(0.0, -10.0)) < 1e-6);
+
+        // containing
+        assert_eq!(
+            circle.intersection(
+                &Vector::zero(),
+                0.0,
+                &small_circle,
+                &Vector::new(1.0, 2.0),
+                0.0
+            ),
+            None
+        );
+        assert_eq!(
+            small_circle.intersection(&Vector::new(1.0, 2.0), 0.0, &ellipse, &Vector::zero(), 30.0),
+            None
+        );
+
+        // disjoint
+        assert_eq!(
+            circle.intersection(&Vector::zero(), 0.0, &ellipse, &Vector::new(0.0, 25.0), 0.0),
+            None
+        );
+        assert_eq!(
+            ellipse.intersection(
+                &Vector::zero(),
+                45.0,
+                &ellipse,
+                &Vector::new(40.0, 0.0),
+                135.0
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn intersect_rotated_ellipses() {
+        let e1 = Shape::Ellipse(Vector::new(2.0, 0.0), 30, 10);
+        let e2 = Shape::Ellipse(Vector::zero(), 25, 8);
+        let e1_pos = Vector::new(3.0, -1.0);
+        let e2_pos = Vector::new(-4.0, 2.0);
+
+        let points = e1.intersection(&e1_pos, 37.0, &e2, &e2_pos, 110.0).unwrap();
+        assert_eq!(points.len(), 4);
+        for point in &points {
+            assert!(
+                CollisionShape::ellipse_value(
+                    point,
+                    &(&e1_pos + Vector::new(2.0, 0.0)),
+                    30.0,
+                    10.0,
+                    37.0
+                )
+                .abs()
+                    < 1e-9
+            );
+            assert!(CollisionShape::ellipse_value(point, &e2_pos, 25.0, 8.0, 110.0).abs() < 1e-9);
+        }
+        assert_eq!(
+            e2.intersection(&e2_pos, 110.0, &e1, &e1_pos, 37.0)
+                .unwrap()
+                .len(),
+            4
+        );
+    }
+
+    #[test]
+    fn exchange_impulses() {
+        assert_eq!(Mass::Infinite.inverse_values(&Mass::Copy), (0.0, 1.0));
+        assert_eq!(
+            Mass::Elastic(2.0).inverse_values(&Mass::Infinite),
+            (0.5, 0.0)
+        );
+        assert_eq!(Mass::Infinite.inverse_values(&Mass::Infinite), (0.0, 0.0));
+        assert_eq!(Mass::Copy.inverse_values(&Mass::Copy), (1.0, 1.0));
+        assert_eq!(Mass::Copy.inverse_values(&Mass::Elastic(5.0)), (0.2, 0.2));
+        assert_eq!(
+            Mass::Elastic(1.0).inverse_values(&Mass::Elastic(4.0)),
+            (1.0, 0.25)
+        );
+        assert_eq!(
+            Mass::Elastic(0.0).inverse_values(&Mass::Elastic(0.0)),
+            (1.0, 1.0)
+        );
+
+        let mut light = ball(
+            Vector::new(-9.0, 0.0),
+            Vector::new(4.0, 0.0),
+            Mass::Elastic(1.0),
+        );
+        let mut heavy = ball(Vector::new(9.0, 0.0), Vector::zero(), Mass::Elastic(3.0));
+        assert!(CollisionBody::collide(&mut light, &mut heavy).is_some());
+        assert_close(&light.velocity, &Vector::new(-2.0, 0.0));
+        assert_close(&heavy.velocity, &Vector::new(2.0, 0.0));
+    }
+
+    #[test]
+    fn ball_bounces_off_wall() {
+        let mut ball = ball(Vector::new(-5.0, 0.0), Vector::new(3.0, 1.0), Mass::Copy);
+        let mut wall = wall(Vector::zero());
+
+        assert!(CollisionBody::collide(&mut wall, &mut ball).is_some());
+        assert_close(&ball.velocity, &Vector::new(-3.0, 1.0));
+        assert_eq!(wall.velocity, Vector::zero());
+
+        assert!(CollisionBody::collide(&mut ball, &mut wall).is_some());
+        assert_close(&ball.velocity, &Vector::new(-3.0, 1.0));
+    }
+
+    #[test]
+    fn material_response() {
+        let body = |shape: Shape, position: Vector, velocity: Vector, behaviour: Mass, material| {
+            BodyBuilder::new()
+                .position(position)
+                .velocity(velocity)
+                .collision(CollisionBody::new(shape, behaviour).material(material))
+                .build()
+        };
+        let circle = Shape::Ellipse(Vector::zero(), 10, 10);
+        let line = Shape::Line(Vector::new(0.0, -100.0), Vector::new(0.0, 100.0));
+        let material = Material::new(0.0, 0.5, 0.25);
+
+        let mut bouncy = body(
+            circle.clone(),
+            Vector::new(-5.0, 0.0),
+            Vector::new(3.0, 1.0),
+            Mass::Copy,
+            Material::new(0.5, 0.0, 0.0),
+        );
+        assert!(CollisionBody::collide(&mut bouncy, &mut wall(Vector::zero())).is_some());
+        assert_close(&bouncy.velocity, &Vector::new(-2.25, 1.0));
+
+        let mut wall = body(
+            line,
+            Vector::zero(),
+            Vector::zero(),
+            Mass::Infinite,
+            material.clone(),
+        );
+        let mut sliding = body(
+            circle.clone(),
+            Vector::new(-5.0, 0.0),
+            Vector::new(3.0, 4.0),
+            Mass::Copy,
+            material.clone(),
+        );
+        assert!(CollisionBody::collide(&mut sliding, &mut wall).is_some());
+        assert_close(&sliding.velocity, &Vector::new(0.0, 3.25));
+
+        let mut sticking = body(
+            circle,
+            Vector::new(-5.0, 0.0),
+            Vector::new(3.0, -1.0),
+            Mass::Copy,
+            material,
+        );
+        assert!(CollisionBody::collide(&mut wall, &mut sticking).is_some());
+        // The ball sticks where it touches the wall and starts rolling instead of sliding
+        assert!(sticking.velocity.x.abs() < 1e-9);
+        assert!(sticking.velocity.y < 0.0 && sticking.velocity.y > -1.0);
+        let rolling = sticking.velocity.y + 5.0 * sticking.rotation_velocity.to_radians();
+        assert!(rolling.abs() < 1e-9, "{}", rolling);
+        assert_eq!(wall.velocity, Vector::zero());
+    }
+
+    #[test]
+    fn boxes_exchange_impulses() {
+        let small_box = |position: Vector, velocity: Vector| {
+            BodyBuilder::new()
+                .position(position)
+                .velocity(velocity)
+                .collision(CollisionBody::new(
+                    Shape::Rect(Vector::zero(), 20, 20),
+                    Mass::Elastic(1.0),
+                ))
+                .build()
+        };
+        let mut b1 = small_box(Vector::new(-9.0, 0.0), Vector::new(2.0, 0.0));
+        let mut b2 = small_box(Vector::new(9.0, 0.0), Vector::new(-1.0, 0.0));
+        let mut b3 = small_box(Vector::new(100.0, 0.0), Vector::new(-1.0, 0.0));
+
+        assert!(CollisionBody::collide(&mut b1, &mut b2).is_some());
+        assert_close(&b1.velocity, &Vector::new(-1.0, 0.0));
+        assert_close(&b2.velocity, &Vector::new(2.0, 0.0));
+        assert!(CollisionBody::collide(&mut b1, &mut b3).is_none());
+    }
+
+    #[test]
+    fn balls_exchange_impulses() {
+        let mut b1 = ball(Vector::new(-9.0, 0.0), Vector::new(2.0, 0.0), Mass::Copy);
+        let mut b2 = ball(Vector::new(9.0, 0.0), Vector::new(-1.0, 0.0), Mass::Copy);
+
+        assert!(CollisionBody::collide(&mut b1, &mut b2).is_some());
+        assert_close(&b1.velocity, &Vector::new(-1.0, 0.0));
+        assert_close(&b2.velocity, &Vector::new(2.0, 0.0));
+        assert!(b1.rotation_velocity.abs() < 1e-9 && b2.rotation_velocity.abs() < 1e-9);
+    }
+
+    #[test]
+    fn off_center_hit_spins() {
+        let mut small_box = BodyBuilder::new()
+            .collision(CollisionBody::new(
+                Shape::Rect(Vector::zero(), 20, 20),
+                Mass::Elastic(1.0),
+            ))
+            .build();
+        let mut b1 = ball(
+            Vector::new(-18.0, -8.0),
+            Vector::new(4.0, 0.0),
+            Mass::Elastic(1.0),
+        );
+
+        assert!(CollisionBody::collide(&mut b1, &mut small_box).is_some());
+        assert!(small_box.velocity.x > 0.0);
+        assert!(b1.velocity.x < 4.0);
+        assert!(
+            small_box.rotation_velocity > 0.0,
+            "{}",
+            small_box.rotation_velocity
+        );
+
+        let momentum = &b1.velocity + &small_box.velocity;
+        assert_close(&momentum, &Vector::new(4.0, 0.0));
+    }
+
+    #[test]
+    fn kinematic_bodies_push() {
+        let mut platform = ball(Vector::new(-9.0, 0.0), Vector::new(2.0, 0.0), Mass::Copy);
+        platform.set_kind(BodyKind::Kinematic);
+        let mut b2 = ball(Vector::new(9.0, 0.0), Vector::new(-1.0, 0.0), Mass::Copy);
+
+        assert!(CollisionBody::collide(&mut platform, &mut b2).is_some());
+        assert_close(&platform.velocity, &Vector::new(2.0, 0.0));
+        assert_close(&platform.position, &Vector::new(-9.0, 0.0));
+        assert_close(&b2.velocity, &Vector::new(5.0, 0.0));
+        assert!(b2.position.x > 9.0);
+
+        let mut level = ball(Vector::new(9.0, 0.0), Vector::zero(), Mass::Copy);
+        level.set_kind(BodyKind::Static);
+        assert!(CollisionBody::collide(&mut platform, &mut level).is_none());
+    }
+
+    #[test]
+    fn sensors_detect_all_kinds() {
+        let mut platform = ball(Vector::new(-9.0, 0.0), Vector::new(2.0, 0.0), Mass::Copy);
+        platform.set_kind(BodyKind::Kinematic);
+        let mut zone = BodyBuilder::new()
+            .position(Vector::new(9.0, 0.0))
+            .collision(
+                CollisionBody::new_solid(Shape::Ellipse(Vector::zero(), 10, 10), Mass::Infinite)
+                    .sensor(),
+            )
+            .kind(BodyKind::Static)
+            .build();
+
+        assert!(CollisionBody::collide(&mut platform, &mut zone).is_some());
+        assert!(CollisionBody::collide(&mut zone, &mut platform).is_some());
+        assert_close(&platform.velocity, &Vector::new(2.0, 0.0));
+        assert_close(&platform.position, &Vector::new(-9.0, 0.0));
+        assert_close(&zone.position, &Vector::new(9.0, 0.0));
+    }
+
+    #[test]
+    fn box_stays_inside_hollow_arena() {
+        let mut arena = BodyBuilder::new()
+            .collision(CollisionBody::new(
+                Shape::Rect(Vector::zero(), 200, 200),
+                Mass::Infinite,
+            ))
+            .build();
+        let mut small_box = BodyBuilder::new()
+            .position(Vector::new(95.0, 0.0))
+            .velocity(Vector::new(3.0, 0.0))
+            .collision(CollisionBody::new(
+                Shape::Rect(Vector::zero(), 20, 20),
+                Mass::Copy,
+            ))
+            .build();
+
+        assert!(CollisionBody::collide(&mut arena, &mut small_box).is_some());
+        assert_close(&small_box.velocity, &Vector::new(-3.0, 0.0));
+    }
+
+    #[test]
+    fn contact_ball_line() {
+        let ball = Shape::Ellipse(Vector::zero(), 10, 10);
+        let line = Shape::Line(Vector::new(0.0, -100.0), Vector::new(0.0, 100.0));
+
+        let contact = ball
+            .contact(&Vector::new(-5.0, 0.0), 0.0, &line, &Vector::zero(), 0.0)
+            .unwrap();
+        assert_close(&contact.normal, &Vector::new(1.0, 0.0));
+        assert!((contact.depth - 5.0).abs() < 1e-9, "{}", contact.depth);
+        assert_eq!(contact.points.len(), 2);
+
+        let contact = line
+            .contact(&Vector::zero(), 0.0, &ball, &Vector::new(-5.0, 0.0), 0.0)
+            .unwrap();
+        assert_close(&contact.normal, &Vector::new(-1.0, 0.0));
+        assert!((contact.depth - 5.0).abs() < 1e-9, "{}", contact.depth);
+
+        assert!(ball
+            .contact(&Vector::new(-20.0, 0.0), 0.0, &line, &Vector::zero(), 0.0)
+            .is_none());
+    }
+
+    #[test]
+    fn contact_boxes() {
+        let small_box = Shape::Rect(Vector::zero(), 20, 20);
+
+        let contact = small_box
+            .contact(
+                &Vector::new(-9.0, 0.0),
+                0.0,
+                &small_box,
+                &Vector::new(9.0, 0.0),
+                0.0,
+            )
+            .unwrap();
+        assert_close(&contact.normal, &Vector::new(1.0, 0.0));
+        assert!((contact.depth - 2.0).abs() < 1e-9, "{}", contact.depth);
+        assert_eq!(contact.points.len(), 4);
+    }
+
+    #[test]
+    fn contact_inside_hollow_arena() {
+        let arena = Shape::Rect(Vector::zero(), 200, 200);
+        let small_box = Shape::Rect(Vector::zero(), 20, 20);
+
+        let contact = arena
+            .contact(
+                &Vector::zero(),
+                0.0,
+                &small_box,
+                &Vector::new(95.0, 0.0),
+                0.0,
+            )
+            .unwrap();
+        assert_close(&contact.normal, &Vector::new(-1.0, 0.0));
+        assert!((contact.depth - 5.0).abs() < 1e-9, "{}", contact.depth);
+    }
+
+    #[test]
+    fn collision_separates_bodies() {
+        let mut b1 = ball(Vector::new(-9.0, 0.0), Vector::zero(), Mass::Elastic(1.0));
+        let mut b2 = ball(Vector::new(9.0, 0.0), Vector::zero(), Mass::Elastic(3.0));
+        let contact = CollisionBody::collide(&mut b1, &mut b2).unwrap();
+        assert!((contact.depth - 2.0).abs() < 1e-6, "{}", contact.depth);
+        assert_close(
+            &b1.position,
+            &(Vector::new(-9.0, 0.0) - contact.depth * 0.75 * &contact.normal),
+        );
+        assert_close(
+            &b2.position,
+            &(Vector::new(9.0, 0.0) + contact.depth * 0.25 * &contact.normal),
+        );
+
+        let mut wall = wall(Vector::zero());
+        let mut b3 = ball(Vector::new(-5.0, 0.0), Vector::zero(), Mass::Copy);
+        CollisionBody::collide(&mut wall, &mut b3).unwrap();
+        assert_eq!(wall.position, Vector::zero());
+        assert_close(&b3.position, &Vector::new(-10.0, 0.0));
+    }
+
+    #[test]
+    fn solid_contains_body() {
+        let block = CollisionBody::new_solid(Shape::Rect(Vector::zero(), 200, 100), Mass::Infinite);
+        let small_box = CollisionBody::new(Shape::Rect(Vector::zero(), 20, 20), Mass::Copy);
+
+        let contact = block
+            .contact(
+                &Vector::zero(),
+                0.0,
+                &small_box,
+                &Vector::new(10.0, 30.0),
+                0.0,
+            )
+            .unwrap();
+        assert_close(&contact.normal, &Vector::new(0.0, 1.0));
+        assert!((contact.depth - 30.0).abs() < 1e-9, "{}", contact.depth);
+        assert_eq!(contact.points.len(), 1);
+        assert!((contact.points[0].y - 20.0).abs() < 1e-9);
+
+        let contact = small_box
+            .contact(&Vector::new(10.0, 30.0), 0.0, &block, &Vector::zero(), 0.0)
+            .unwrap();
+        assert_close(&contact.normal, &Vector::new(0.0, -1.0));
+        assert!((contact.depth - 30.0).abs() < 1e-9, "{}", contact.depth);
+
+        let hollow_block =
+            CollisionBody::new(Shape::Rect(Vector::zero(), 200, 100), Mass::Infinite);
+        assert!(hollow_block
+            .contact(
+                &Vector::zero(),
+                0.0,
+                &small_box,
+                &Vector::new(10.0, 30.0),
+                0.0
+            )
+            .is_none());
+        assert!(block
+            .contact(
+                &Vector::zero(),
+                0.0,
+                &small_box,
+                &Vector::new(0.0, 100.0),
+                0.0
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn solid_balls_overlap() {
+        let ball = CollisionBody::new_solid(Shape::Ellipse(Vector::zero(), 10, 10), Mass::Copy);
+        let pixel = CollisionBody::new(Shape::Pixel(Vector::zero()), Mass::Copy);
+
+        let contact = ball
+            .contact(
+                &Vector::new(-8.0, 0.0),
+                0.0,
+                &ball,
+                &Vector::new(8.0, 0.0),
+                0.0,
+            )
+            .unwrap();
+        assert!(contact.normal.distance(&Vector::new(1.0, 0.0)) < 1e-4);
+        assert!((contact.depth - 4.0).abs() < 1e-6, "{}", contact.depth);
+        assert_eq!(contact.points.len(), 2);
+
+        let contact = ball
+            .contact(&Vector::zero(), 0.0, &pixel, &Vector::new(-7.0, 0.0), 0.0)
+            .unwrap();
+        assert!(contact.normal.distance(&Vector::new(-1.0, 0.0)) < 1e-4);
+        assert!((contact.depth - 3.0).abs() < 1e-6, "{}", contact.depth);
+    }
+
+    #[test]
+    fn intersect_polygons() {
+        let square = Shape::Polygon(vec![
+            Vector::new(-10.0, -10.0),
+            Vector::new(10.0, -10.0),
+            Vector::new(10.0, 10.0),
+            Vector::new(-10.0, 10.0),
+        ]);
+        let intersections = |other: &Shape, other_pos: Vector, other_degree: f64| {
+            square.intersection(&Vector::zero(), 0.0, other, &other_pos, other_degree)
+        };
+
+        let line = Shape::Line(Vector::new(0.0, -20.0), Vector::new(0.0, 20.0));
+        assert_eq!(
+            intersections(&line, Vector::zero(), 0.0),
+            Some(vec![Vector::new(0.0, -10.0), Vector::new(0.0, 10.0)])
+        );
+        assert_eq!(
+            intersections(&Shape::Pixel(Vector::new(10.0, 0.0)), Vector::zero(), 0.0),
+            Some(vec![Vector::new(10.0, 0.0)])
+        );
+        assert_eq!(
+            intersections(&Shape::Pixel(Vector::zero()), Vector::zero(), 0.0),
+            None
+        );
+        assert_eq!(
+            intersections(
+                &Shape::Rect(Vector::zero(), 20, 20),
+                Vector::new(15.0, 15.0),
+                0.0
+            ),
+            Some(vec![Vector::new(10.0, 5.0), Vector::new(5.0, 10.0)])
+        );
+
+        let points = intersections(
+            &Shape::Ellipse(Vector::zero(), 10, 10),
+            Vector::new(15.0, 0.0),
+            0.0,
+        )
+        .unwrap();
+        assert_eq!(points.len(), 2);
+        for point in points {
+            assert!((point.x - 10.0).abs() < 1e-9);
+            assert!((point.y.abs() - 75f64.sqrt()).abs() < 1e-9);
+        }
+
+        let diamond_points = square
+            .intersection(&Vector::new(20.0, 0.0), 45.0, &square, &Vector::zero(), 0.0)
+            .unwrap();
+        let reach = 200f64.sqrt() - 10.0;
+        assert_eq!(diamond_points.len(), 2);
+        for point in diamond_points {
+            assert!((point.x - 10.0).abs() < 1e-9);
+            assert!((point.y.abs() - reach).abs() < 1e-9);
+        }
+        assert_eq!(
+            square.intersection(&Vector::new(40.0, 0.0), 45.0, &square, &Vector::zero(), 0.0),
+            None
+        );
+    }
+
+    #[test]
+    fn polygon_separating_axis() {
+        let square = Shape::Polygon(vec![
+            Vector::new(-10.0, -10.0),
+            Vector::new(10.0, -10.0),
+            Vector::new(10.0, 10.0),
+            Vector::new(-10.0, 10.0),
+        ]);
+        let (normal, depth) = square
+            .separating_axis(&Vector::zero(), 0.0, &square, &Vector::new(16.0, 1.0), 0.0)
+            .unwrap();
+        assert_close(&normal, &Vector::new(1.0, 0.0));
+        assert!((depth - 4.0).abs() < 1e-9);
+
+        let (normal, depth) = square
+            .separating_axis(&Vector::new(16.0, 1.0), 0.0, &square, &Vector::zero(), 0.0)
+            .unwrap();
+        assert_close(&normal, &Vector::new(-1.0, 0.0));
+        assert!((depth - 4.0).abs() < 1e-9);
+
+        let circle = Shape::Ellipse(Vector::zero(), 5, 5);
+        assert!(square
+            .separating_axis(&Vector::zero(), 0.0, &circle, &Vector::new(14.0, 14.0), 0.0)
+            .is_none());
+        assert!(square
+            .separating_axis(&Vector::zero(), 0.0, &circle, &Vector::new(12.0, 12.0), 0.0)
+            .is_some());
+
+        let contact = square
+            .solid_contact(&Vector::zero(), 0.0, &square, &Vector::new(16.0, 1.0), 0.0)
+            .unwrap();
+        assert_close(&contact.normal, &Vector::new(1.0, 0.0));
+        assert!((contact.depth - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn contains_point() {
+        let rect = Shape::Rect(Vector::zero(), 20, 10);
+        assert!(rect.contains(&Vector::new(5.0, 5.0), 90.0, &Vector::new(8.0, 13.0)));
+        assert!(!rect.contains(&Vector::new(5.0, 5.0), 90.0, &Vector::new(13.0, 8.0)));
+
+        let ellipse = Shape::Ellipse(Vector::new(10.0, 0.0), 10, 5);
+        assert!(ellipse.contains(&Vector::zero(), 0.0, &Vector::new(19.0, 0.0)));
+        assert!(!ellipse.contains(&Vector::zero(), 90.0, &Vector::new(19.0, 0.0)));
+
+        let triangle = Shape::Polygon(vec![
+            Vector::new(0.0, 0.0),
+            Vector::new(10.0, 0.0),
+            Vector::new(0.0, 10.0),
+        ]);
+        assert!(triangle.contains(&Vector::zero(), 0.0, &Vector::new(4.0, 4.0)));
+        assert!(!triangle.contains(&Vector::zero(), 0.0, &Vector::new(6.0, 6.0)));
+
+        let line = Shape::Line(Vector::new(-10.0, 0.0), Vector::new(10.0, 0.0));
+        assert!(line.contains(&Vector::zero(), 0.0, &Vector::new(3.0, 0.4)));
+        assert!(!line.contains(&Vector::zero(), 0.0, &Vector::new(10.6, 0.0)));
+        assert!(Shape::Pixel(Vector::zero()).contains(
+            &Vector::new(1.0, 1.0),
+            0.0,
+            &Vector::new(1.2, 1.2)
+        ));
+    }
+
+    #[test]
+    fn raycast_shapes() {
+        let right = Vector::new(1.0, 0.0);
+        let circle = Shape::Ellipse(Vector::zero(), 10, 10);
+        let (point, normal, distance) = circle
+            .raycast(&Vector::new(20.0, 0.0), 0.0, &Vector::zero(), &right, 100.0)
+            .unwrap();
+        assert_close(&point, &Vector::new(10.0, 0.0));
+        assert_close(&normal, &Vector::new(-1.0, 0.0));
+        assert!((distance - 10.0).abs() < 1e-9);
+        assert_eq!(
+            circle.raycast(&Vector::new(20.0, 0.0), 0.0, &Vector::zero(), &right, 5.0),
+            None
+        );
+
+        let (point, normal, distance) = circle
+            .raycast(&Vector::zero(), 0.0, &Vector::zero(), &right, 100.0)
+            .unwrap();
+        assert_close(&point, &Vector::new(10.0, 0.0));
+        assert_close(&normal, &Vector::new(-1.0, 0.0));
+        assert!((distance - 10.0).abs() < 1e-9);
+
+        let rect = Shape::Rect(Vector::zero(), 20, 20);
+        let (point, normal, _) = rect
+            .raycast(
+                &Vector::new(0.0, 30.0),
+                0.0,
+                &Vector::new(5.0, 0.0),
+                &Vector::new(0.0, 1.0),
+                100.0,
+            )
+            .unwrap();
+        assert_close(&point, &Vector::new(5.0, 20.0));
+        assert_close(&normal, &Vector::new(0.0, -1.0));
+    }
+
+    #[test]
+    fn overlapping_shapes() {
+        let rect = Shape::Rect(Vector::zero(), 20, 20);
+        let ball = Shape::Ellipse(Vector::zero(), 2, 2);
+        let line = Shape::Line(Vector::new(-5.0, 0.0), Vector::new(5.0, 0.0));
+        assert!(rect.overlaps(&Vector::zero(), 0.0, &ball, &Vector::new(11.0, 0.0), 0.0));
+        assert!(rect.overlaps(&Vector::zero(), 0.0, &ball, &Vector::new(3.0, 3.0), 0.0));
+        assert!(ball.overlaps(&Vector::new(3.0, 3.0), 0.0, &rect, &Vector::zero(), 0.0));
+        assert!(!rect.overlaps(&Vector::zero(), 0.0, &ball, &Vector::new(13.0, 0.0), 0.0));
+        assert!(line.overlaps(&Vector::zero(), 0.0, &rect, &Vector::zero(), 45.0));
+        assert!(!line.overlaps(&Vector::new(0.0, 12.0), 0.0, &rect, &Vector::zero(), 0.0));
+    }
+
+    #[test]
+    fn ball_stays_inside_hollow_ring() {
+        let ring = CollisionBody::new(Shape::Ellipse(Vector::zero(), 100, 100), Mass::Infinite);
+        let ball = CollisionBody::new_solid(Shape::Ellipse(Vector::zero(), 10, 10), Mass::Copy);
+
+        assert!(ring
+            .contact(&Vector::zero(), 0.0, &ball, &Vector::new(50.0, 0.0), 0.0)
+            .is_none());
+        let contact = ring
+            .contact(&Vector::zero(), 0.0, &ball, &Vector::new(95.0, 0.0), 0.0)
+            .unwrap();
+        assert_close(&contact.normal, &Vector::new(-1.0, 0.0));
+    }
+
+    #[test]
+    fn sensor_does_not_respond() {
+        let mut zone = BodyBuilder::new()
+            .collision(
+                CollisionBody::new_solid(Shape::Rect(Vector::zero(), 100, 100), Mass::Infinite)
+                    .sensor(),
+            )
+            .build();
+        let mut ball = ball(Vector::new(10.0, 0.0), Vector::new(3.0, 1.0), Mass::Copy);
+
+        let contact = CollisionBody::collide(&mut zone, &mut ball).unwrap();
+        assert!(contact.depth > 0.0);
+        assert_eq!(ball.position, Vector::new(10.0, 0.0));
+        assert_eq!(ball.velocity, Vector::new(3.0, 1.0));
+        assert_eq!(zone.position, Vector::zero());
+
+        ball.position = Vector::new(100.0, 0.0);
+        assert!(CollisionBody::collide(&mut zone, &mut ball).is_none());
+    }
+
     #[test]
     fn add_to_layer() {
-        let b1 = RefBody::new(1.0, Vector::zero(), Vector::zero(), 0.0, 0.0, None, None);
-        let b2 = RefBody::new(
+        let mut bodies = BodySet::new();
+        let b1 = bodies.insert(Body::new(
+            1.0,
+            Vector::zero(),
+            Vector::zero(),
+            0.0,
+            0.0,
+            None,
+            None,
+        ));
+        let b2 = bodies.insert(Body::new(
             2.0,
             Vector::scalar(20.0),
             Vector::zero(),
@@ -771,8 +2792,8 @@ mod tests {
             0.0,
             None,
             None,
-        );
-        let b3 = RefBody::new(
+        ));
+        let b3 = bodies.insert(Body::new(
             3.0,
             Vector::scalar(100.0),
             Vector::zero(),
@@ -780,8 +2801,8 @@ mod tests {
             0.0,
             None,
             None,
-        );
-        let b4 = RefBody::new(
+        ));
+        let b4 = bodies.insert(Body::new(
             4.0,
             Vector::new(0.0, 20.0),
             Vector::zero(),
@@ -789,24 +2810,138 @@ mod tests {
             0.0,
             None,
             None,
-        );
+        ));
 
         let mut collision_layer =
             CollisionLayer::new(PhysicalSize::new(100, 100), PhysicalSize::new(10, 10));
-        assert!(collision_layer.add_body(b1.clone(), true));
-        assert!(collision_layer.add_body(b2.clone(), false));
-        assert!(collision_layer.add_body(b3.clone(), true));
-        assert!(collision_layer.add_body(b4.clone(), false));
-        assert!(!collision_layer.add_body(b3.clone(), false));
+        assert!(collision_layer.add_body(b1, &bodies, true));
+        assert!(collision_layer.add_body(b2, &bodies, false));
+        assert!(collision_layer.add_body(b3, &bodies, true));
+        assert!(collision_layer.add_body(b4, &bodies, false));
+        assert!(!collision_layer.add_body(b3, &bodies, false));
 
         println!("{:?}", collision_layer);
-        assert!(!collision_layer.remove_body(&b3, Some(false)));
-        assert!(collision_layer.remove_body(&b3, Some(true)));
-        assert!(!collision_layer.remove_body(&b3, Some(false)));
-        assert!(!collision_layer.remove_body(&b3, Some(true)));
-        assert!(collision_layer.remove_body(&b2, Some(false)));
-        assert!(!collision_layer.remove_body(&b2, None));
-        assert!(collision_layer.remove_body(&b1, None));
-        assert!(collision_layer.remove_body(&b4, None));
+        assert!(!collision_layer.remove_body(b3, Some(false)));
+        assert!(collision_layer.remove_body(b3, Some(true)));
+        assert!(!collision_layer.remove_body(b3, Some(false)));
+        assert!(!collision_layer.remove_body(b3, Some(true)));
+        assert!(collision_layer.remove_body(b2, Some(false)));
+        assert!(!collision_layer.remove_body(b2, None));
+        assert!(collision_layer.remove_body(b1, None));
+        assert!(collision_layer.remove_body(b4, None));
+
+        bodies.remove(b1);
+        assert!(!collision_layer.add_body(b1, &bodies, true));
+    }
+
+    #[test]
+    fn update_in_layer() {
+        let mut bodies = BodySet::new();
+        let b1 = bodies.insert(Body::new(
+            1.0,
+            Vector::zero(),
+            Vector::zero(),
+            0.0,
+            0.0,
+            None,
+            None,
+        ));
+        let b2 = bodies.insert(Body::new(
+            1.0,
+            Vector::zero(),
+            Vector::zero(),
+            0.0,
+            0.0,
+            None,
+            None,
+        ));
+
+        let mut collision_layer =
+            CollisionLayer::new(PhysicalSize::new(100, 100), PhysicalSize::new(10, 10));
+        assert!(collision_layer.add_body(b1, &bodies, false));
+
+        bodies[b1].position = Vector::scalar(50.0);
+        bodies[b2].position = Vector::scalar(50.0);
+        assert!(collision_layer.update_body(b1, &bodies));
+        assert!(!collision_layer.update_body(b2, &bodies));
+        assert!(collision_layer
+            .collision_grid
+            .contains(&b1, &Vector::scalar(50.0)));
+        assert!(!collision_layer
+            .collision_grid
+            .contains(&b1, &Vector::zero()));
+
+        assert!(!collision_layer.remove_body(b2, None));
+        assert!(collision_layer.remove_body(b1, None));
+    }
+
+    #[test]
+    fn long_wall_spans_cells() {
+        let mut bodies = BodySet::new();
+        let wall = bodies.insert(wall(Vector::new(50.0, 100.0)));
+        let ball = bodies.insert(ball(
+            Vector::new(45.0, 190.0),
+            Vector::new(3.0, 0.0),
+            Mass::Copy,
+        ));
+
+        let mut collision_layer =
+            CollisionLayer::new(PhysicalSize::new(200, 200), PhysicalSize::new(20, 20));
+        assert!(collision_layer.add_body(wall, &bodies, true));
+        assert!(collision_layer.add_body(ball, &bodies, false));
+        for y in [0.0, 100.0, 200.0] {
+            assert!(collision_layer
+                .collision_grid
+                .contains(&wall, &Vector::new(50.0, y)));
+        }
+
+        collision_layer.resolve_collisions(&mut bodies);
+        assert_close(&bodies[ball].velocity, &Vector::new(-3.0, 0.0));
+        assert_close(&bodies[ball].position, &Vector::new(40.0, 190.0));
+        assert!(collision_layer
+            .collision_grid
+            .contains(&ball, &Vector::new(30.0, 180.0)));
+    }
+
+    #[test]
+    fn candidate_pairs() {
+        let mut bodies = BodySet::new();
+        let mut body = |position: Vector| bodies.insert(ball(position, Vector::zero(), Mass::Copy));
+        let o1 = body(Vector::new(15.0, 15.0));
+        let o2 = body(Vector::new(25.0, 15.0));
+        let o3 = body(Vector::new(185.0, 185.0));
+        let a1 = body(Vector::new(15.0, 25.0));
+        let a2 = body(Vector::new(20.0, 20.0));
+        let a3 = body(Vector::new(185.0, 15.0));
+
+        let mut collision_layer =
+            CollisionLayer::new(PhysicalSize::new(200, 200), PhysicalSize::new(20, 20));
+        for obstacle in [o1, o2, o3] {
+            collision_layer.add_body(obstacle, &bodies, true);
+        }
+        for actor in [a1, a2, a3] {
+            collision_layer.add_body(actor, &bodies, false);
+        }
+
+        let mut pairs: Vec<(BodyHandle, BodyHandle)> =
+            collision_layer.candidate_pairs(&bodies).collect();
+        assert_eq!(pairs.len(), 5);
+        assert!(pairs.windows(2).all(|pair| pair[0] < pair[1]));
+        for (obstacle, other) in [(o1, a1), (o1, a2), (o2, a1), (o2, a2)] {
+            assert!(pairs.contains(&(obstacle, other)));
+        }
+        pairs.retain(|(obstacle, other)| {
+            (*obstacle == o1 && *other == o2) || (*obstacle == o2 && *other == o1)
+        });
+        assert_eq!(pairs.len(), 1);
+
+        for handle in [o1, a1] {
+            bodies[handle].fall_asleep();
+        }
+        let pairs: Vec<(BodyHandle, BodyHandle)> =
+            collision_layer.candidate_pairs(&bodies).collect();
+        assert_eq!(pairs.len(), 4);
+        assert!(!pairs.contains(&(o1, a1)));
+        assert!(pairs.contains(&(o1, a2)));
     }
 }
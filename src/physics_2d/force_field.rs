@@ -0,0 +1,121 @@
+use crate::math_2d::Vector;
+
+use super::Body;
+
+///A force acting on every body of a `World`, added with `World::add_force_field`.
+///
+///The forces of all fields are summed up and accelerate the bodies during the next `World::step`. Closures taking a `&Body<Vector>` and returning a `Vector` are force fields as well.
+pub trait ForceField: Send + Sync {
+    ///The force acting on `body` at its current position
+    fn force(&self, body: &Body<Vector>) -> Vector;
+}
+impl<F: Fn(&Body<Vector>) -> Vector + Send + Sync> ForceField for F {
+    fn force(&self, body: &Body<Vector>) -> Vector {
+        self(body)
+    }
+}
+
+///Pulls bodies towards `position`, proportional to their `mass` and inversely to their squared distance.
+///
+///A negative `strength` pushes bodies away instead. Inside of `min_distance` the force stops growing, so bodies passing `position` are not flung away.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointAttractor {
+    pub position: Vector,
+    pub strength: f64,
+    pub min_distance: f64,
+}
+impl PointAttractor {
+    pub fn new(position: Vector, strength: f64, min_distance: f64) -> Self {
+        PointAttractor {
+            position,
+            strength,
+            min_distance,
+        }
+    }
+}
+impl ForceField for PointAttractor {
+    fn force(&self, body: &Body<Vector>) -> Vector {
+        let offset = &self.position - &body.position;
+        let distance = offset.length().max(self.min_distance);
+        offset.normalize() * (self.strength * body.mass / (distance * distance))
+    }
+}
+
+///Pushes every body, whose position lies inside the axis aligned rectangle from `min` to `max`, by the constant `force`
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindZone {
+    pub min: Vector,
+    pub max: Vector,
+    pub force: Vector,
+}
+impl WindZone {
+    pub fn new(min: Vector, max: Vector, force: Vector) -> Self {
+        WindZone { min, max, force }
+    }
+}
+impl ForceField for WindZone {
+    fn force(&self, body: &Body<Vector>) -> Vector {
+        let position = &body.position;
+        if (self.min.x..=self.max.x).contains(&position.x)
+            && (self.min.y..=self.max.y).contains(&position.y)
+        {
+            self.force.clone()
+        } else {
+            Vector::zero()
+        }
+    }
+}
+
+///Slows bodies down with a force of `coefficient` times their velocity against their movement
+#[derive(Debug, Clone, PartialEq)]
+pub struct Drag {
+    pub coefficient: f64,
+}
+impl Drag {
+    pub fn new(coefficient: f64) -> Self {
+        Drag { coefficient }
+    }
+}
+impl ForceField for Drag {
+    fn force(&self, body: &Body<Vector>) -> Vector {
+        &body.velocity * -self.coefficient
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::physics_2d::BodyBuilder;
+
+    use super::*;
+
+    #[test]
+    fn force_fields() {
+        let body = BodyBuilder::new()
+            .mass(2.0)
+            .position(Vector::new(10.0, 0.0))
+            .velocity(Vector::new(3.0, -1.0))
+            .build();
+
+        let attractor = PointAttractor::new(Vector::zero(), 50.0, 1.0);
+        assert_eq!(attractor.force(&body), Vector::new(-1.0, 0.0));
+        let close = PointAttractor::new(Vector::new(10.0, 0.5), 1.0, 1.0);
+        assert_eq!(close.force(&body), Vector::new(0.0, 2.0));
+
+        let wind = WindZone::new(
+            Vector::zero(),
+            Vector::new(20.0, 20.0),
+            Vector::new(0.0, 5.0),
+        );
+        assert_eq!(wind.force(&body), Vector::new(0.0, 5.0));
+        let far = WindZone::new(
+            Vector::scalar(50.0),
+            Vector::scalar(60.0),
+            Vector::new(0.0, 5.0),
+        );
+        assert_eq!(far.force(&body), Vector::zero());
+
+        assert_eq!(Drag::new(0.5).force(&body), Vector::new(-1.5, 0.5));
+        let closure = |body: &Body<Vector>| Vector::new(body.mass, 0.0);
+        assert_eq!(closure.force(&body), Vector::new(2.0, 0.0));
+    }
+}
@@ -0,0 +1,110 @@
+/// Variants describing how the values of two colliding `Material`s are combined
+///
+/// * `Average`: The mean of both values
+/// * `Minimum`: The smaller value
+/// * `Multiply`: The product of both values
+/// * `Maximum`: The bigger value
+///
+/// If the `Material`s use different rules, the one listed last wins.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy, Default)]
+pub enum CombineRule {
+    #[default]
+    Average,
+    Minimum,
+    Multiply,
+    Maximum,
+}
+impl CombineRule {
+    fn combine(&self, value: f64, other_value: f64) -> f64 {
+        match self {
+            CombineRule::Average => (value + other_value) / 2.0,
+            CombineRule::Minimum => value.min(other_value),
+            CombineRule::Multiply => value * other_value,
+            CombineRule::Maximum => value.max(other_value),
+        }
+    }
+}
+
+///Surface properties of a `CollisionBody`, used when it collides with another one.
+///
+///* `restitution`: Share of the speed along the contact normal kept after a bounce. 1 bounces perfectly elastic, 0 stops the bodies
+///* `static_friction`: Up to this ratio of the normal impulse, sliding along the contact is stopped completely
+///* `dynamic_friction`: Ratio of the normal impulse, that slows down bodies sliding along each other otherwise
+///
+///The default is perfectly elastic without friction.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Material {
+    pub restitution: f64,
+    pub static_friction: f64,
+    pub dynamic_friction: f64,
+    pub restitution_combine: CombineRule,
+    pub friction_combine: CombineRule,
+}
+impl Material {
+    ///Creates a `Material` using `CombineRule::Average` for all values
+    pub fn new(restitution: f64, static_friction: f64, dynamic_friction: f64) -> Self {
+        Material {
+            restitution,
+            static_friction,
+            dynamic_friction,
+            restitution_combine: CombineRule::default(),
+            friction_combine: CombineRule::default(),
+        }
+    }
+
+    pub fn restitution_combine(mut self, rule: CombineRule) -> Self {
+        self.restitution_combine = rule;
+        self
+    }
+
+    pub fn friction_combine(mut self, rule: CombineRule) -> Self {
+        self.friction_combine = rule;
+        self
+    }
+
+    ///The `Material` of the contact between `self` and `other`, whose values are combined by the winning `CombineRule`s
+    pub fn combine(&self, other: &Material) -> Material {
+        let restitution_combine = self.restitution_combine.max(other.restitution_combine);
+        let friction_combine = self.friction_combine.max(other.friction_combine);
+        Material {
+            restitution: restitution_combine.combine(self.restitution, other.restitution),
+            static_friction: friction_combine.combine(self.static_friction, other.static_friction),
+            dynamic_friction: friction_combine
+                .combine(self.dynamic_friction, other.dynamic_friction),
+            restitution_combine,
+            friction_combine,
+        }
+    }
+}
+impl Default for Material {
+    fn default() -> Self {
+        Material::new(1.0, 0.0, 0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combine_materials() {
+        let rubber = Material::new(0.8, 1.0, 0.8);
+        let ice = Material::new(0.2, 0.1, 0.0).friction_combine(CombineRule::Minimum);
+
+        let contact = rubber.combine(&Material::default());
+        assert_eq!(contact.restitution, 0.9);
+        assert_eq!(contact.static_friction, 0.5);
+        assert_eq!(contact.dynamic_friction, 0.4);
+
+        let contact = rubber.combine(&ice);
+        assert_eq!(contact, ice.combine(&rubber));
+        assert_eq!(contact.restitution, 0.5);
+        assert_eq!(contact.static_friction, 0.1);
+        assert_eq!(contact.dynamic_friction, 0.0);
+
+        let bouncy = rubber.restitution_combine(CombineRule::Maximum);
+        assert_eq!(bouncy.combine(&ice).restitution, 0.8);
+        let sticky = ice.friction_combine(CombineRule::Multiply);
+        assert!((sticky.combine(&sticky).static_friction - 0.01).abs() < 1e-12);
+    }
+}
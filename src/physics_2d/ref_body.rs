@@ -1,12 +1,26 @@
-use std::ops::Deref;
-use std::{cell::RefCell, hash::Hash, rc::Rc};
+use std::cell::RefCell;
+use std::hash::Hash;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 use crate::math_2d::Vector;
 
-use super::{Body, CollisionBody, VisualShape};
+use super::{Body, BodyHandle, BodySet, CollisionBody, VisualShape};
 
-#[derive(Debug, Clone, Eq)]
-pub struct RefBody(Rc<RefCell<Body<Vector>>>);
+///Shared reference to a body stored in a `BodySet`, usually the one of a `World`.
+///
+///It is a `BodyHandle` together with the set it belongs to, so it can be sent to other threads.
+///Two `RefBody`s are equal, if their bodies share the same `uuid`.
+///
+///Borrowing locks the whole set. While another thread holds the lock, borrowing blocks until it is released.
+///Like with a `RefCell`, borrowing mutably on the thread, that already holds a borrow of the same set, or borrowing while it holds a mutable one, would deadlock and panics instead.
+///This includes borrowing from inside of `World::step`, e.g. in a `ForceField`.
+///Borrowing a body, that got removed from its set, panics.
+#[derive(Debug, Clone)]
+pub struct RefBody {
+    handle: BodyHandle,
+    bodies: Arc<RwLock<BodySet>>,
+}
 
 impl RefBody {
     pub fn new(
@@ -30,6 +44,66 @@ impl RefBody {
         .into()
     }
 
+    pub(crate) fn from_handle(handle: BodyHandle, bodies: Arc<RwLock<BodySet>>) -> Self {
+        RefBody { handle, bodies }
+    }
+
+    pub fn handle(&self) -> BodyHandle {
+        self.handle
+    }
+
+    ///Returns `false` once the body got removed from its set
+    pub fn is_alive(&self) -> bool {
+        self.read().1.contains(self.handle)
+    }
+
+    ///Whether the body is stored in `bodies`
+    pub(crate) fn belongs_to(&self, bodies: &Arc<RwLock<BodySet>>) -> bool {
+        Arc::ptr_eq(&self.bodies, bodies)
+    }
+
+    fn read(&self) -> (LockMarker, RwLockReadGuard<'_, BodySet>) {
+        let marker = LockMarker::new(&self.bodies, false);
+        let bodies = self.bodies.read().unwrap_or_else(PoisonError::into_inner);
+        (marker, bodies)
+    }
+
+    fn write(&self) -> (LockMarker, RwLockWriteGuard<'_, BodySet>) {
+        let marker = LockMarker::new(&self.bodies, true);
+        let bodies = self.bodies.write().unwrap_or_else(PoisonError::into_inner);
+        (marker, bodies)
+    }
+
+    pub fn borrow(&self) -> BodyRef<'_> {
+        self.try_borrow()
+            .expect("The body of the RefBody got removed")
+    }
+
+    ///Like `borrow`, but returns `None` if the body got removed
+    pub fn try_borrow(&self) -> Option<BodyRef<'_>> {
+        let (_marker, bodies) = self.read();
+        bodies.contains(self.handle).then_some(BodyRef {
+            bodies,
+            _marker,
+            handle: self.handle,
+        })
+    }
+
+    pub fn borrow_mut(&self) -> BodyRefMut<'_> {
+        self.try_borrow_mut()
+            .expect("The body of the RefBody got removed")
+    }
+
+    ///Like `borrow_mut`, but returns `None` if the body got removed
+    pub fn try_borrow_mut(&self) -> Option<BodyRefMut<'_>> {
+        let (_marker, bodies) = self.write();
+        bodies.contains(self.handle).then_some(BodyRefMut {
+            bodies,
+            _marker,
+            handle: self.handle,
+        })
+    }
+
     pub fn mass(&self) -> f64 {
         self.borrow().mass
     }
@@ -49,47 +123,129 @@ impl RefBody {
     pub fn has_collision(&self) -> bool {
         self.borrow().has_collision()
     }
+
+    pub fn apply_force(&self, force: &Vector) {
+        self.borrow_mut().apply_force(force)
+    }
+    pub fn apply_torque(&self, torque: f64) {
+        self.borrow_mut().apply_torque(torque)
+    }
+    pub fn apply_impulse(&self, impulse: &Vector) {
+        self.borrow_mut().apply_impulse(impulse)
+    }
+    pub fn apply_impulse_at_point(&self, impulse: &Vector, point: &Vector) {
+        self.borrow_mut().apply_impulse_at_point(impulse, point)
+    }
+
+    pub fn is_sleeping(&self) -> bool {
+        self.borrow().is_sleeping()
+    }
+    pub fn wake_up(&self) {
+        self.borrow_mut().wake_up()
+    }
+}
+
+///Immutable borrow of the body of a `RefBody`. Keeps its `BodySet` locked for reading
+pub struct BodyRef<'a> {
+    bodies: RwLockReadGuard<'a, BodySet>,
+    _marker: LockMarker,
+    handle: BodyHandle,
+}
+impl Deref for BodyRef<'_> {
+    type Target = Body<Vector>;
+
+    fn deref(&self) -> &Body<Vector> {
+        &self.bodies[self.handle]
+    }
+}
+
+///Mutable borrow of the body of a `RefBody`. Keeps its `BodySet` locked for writing
+pub struct BodyRefMut<'a> {
+    bodies: RwLockWriteGuard<'a, BodySet>,
+    _marker: LockMarker,
+    handle: BodyHandle,
+}
+impl Deref for BodyRefMut<'_> {
+    type Target = Body<Vector>;
+
+    fn deref(&self) -> &Body<Vector> {
+        &self.bodies[self.handle]
+    }
+}
+impl DerefMut for BodyRefMut<'_> {
+    fn deref_mut(&mut self) -> &mut Body<Vector> {
+        &mut self.bodies[self.handle]
+    }
+}
+
+thread_local! {
+    ///Addresses of the `BodySet`s locked by the current thread and whether they are locked for writing
+    static LOCKED_SETS: RefCell<Vec<(*const RwLock<BodySet>, bool)>> = const { RefCell::new(Vec::new()) };
+}
+
+///Marks a `BodySet` as locked by the current thread, as long as it is alive
+pub(crate) struct LockMarker(*const RwLock<BodySet>);
+impl LockMarker {
+    ///Panics, if the current thread already locked `bodies` for writing or, if `write` is set, at all
+    pub(crate) fn new(bodies: &Arc<RwLock<BodySet>>, write: bool) -> Self {
+        let bodies = Arc::as_ptr(bodies);
+        LOCKED_SETS.with(|locked| {
+            let mut locked = locked.borrow_mut();
+            if locked
+                .iter()
+                .any(|(locked, locked_write)| *locked == bodies && (write || *locked_write))
+            {
+                panic!("The BodySet of the RefBody is already borrowed by this thread");
+            }
+            locked.push((bodies, write));
+        });
+        LockMarker(bodies)
+    }
+}
+impl Drop for LockMarker {
+    fn drop(&mut self) {
+        LOCKED_SETS.with(|locked| {
+            let mut locked = locked.borrow_mut();
+            if let Some(index) = locked.iter().position(|(bodies, _)| *bodies == self.0) {
+                locked.swap_remove(index);
+            }
+        });
+    }
 }
 
 impl PartialEq for RefBody {
     fn eq(&self, other: &Self) -> bool {
-        *self.0.borrow() == *other.0.borrow()
+        if Arc::ptr_eq(&self.bodies, &other.bodies) {
+            return self.handle == other.handle;
+        }
+        let uuid = self.borrow().uuid;
+        uuid == other.borrow().uuid
     }
 }
+impl Eq for RefBody {}
 impl PartialEq<Body<Vector>> for RefBody {
     fn eq(&self, other: &Body<Vector>) -> bool {
-        *self.0.borrow() == other
+        *self.borrow() == other
     }
 }
 impl PartialEq<&Body<Vector>> for RefBody {
     fn eq(&self, other: &&Body<Vector>) -> bool {
-        &*self.0.borrow() == other
+        &*self.borrow() == other
     }
 }
 
 impl From<Body<Vector>> for RefBody {
+    ///Stores the body in a new `BodySet` of its own
     fn from(val: Body<Vector>) -> Self {
-        RefBody(Rc::new(RefCell::new(val)))
-    }
-}
-
-impl From<Rc<RefCell<Body<Vector>>>> for RefBody {
-    fn from(val: Rc<RefCell<Body<Vector>>>) -> Self {
-        RefBody(val)
-    }
-}
-
-impl Deref for RefBody {
-    type Target = RefCell<Body<Vector>>;
-
-    fn deref(&self) -> &RefCell<Body<Vector>> {
-        &self.0
+        let mut bodies = BodySet::new();
+        let handle = bodies.insert(val);
+        RefBody::from_handle(handle, Arc::new(RwLock::new(bodies)))
     }
 }
 
 impl Hash for RefBody {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.0.borrow().hash(state)
+        self.borrow().hash(state)
     }
 }
 
@@ -120,4 +276,77 @@ mod tests {
         assert_eq!(ref_b1, ref_b1_3);
         assert_eq!(&ref_b1, &ref_b1_3);
     }
+
+    #[test]
+    fn shared_set() {
+        let mut bodies = BodySet::new();
+        let handle = bodies.insert(Body::new(
+            1.0,
+            Vector::zero(),
+            Vector::zero(),
+            0.0,
+            0.0,
+            None,
+            None,
+        ));
+        let bodies = Arc::new(RwLock::new(bodies));
+        let body = RefBody::from_handle(handle, bodies.clone());
+
+        let moved = body.clone();
+        std::thread::spawn(move || moved.borrow_mut().position = Vector::new(1.0, 2.0))
+            .join()
+            .unwrap();
+        assert_eq!(body.position(), Vector::new(1.0, 2.0));
+        assert_eq!(body.handle(), handle);
+
+        body.apply_impulse(&Vector::new(2.0, 0.0));
+        assert_eq!(body.velocity(), Vector::new(2.0, 0.0));
+
+        bodies.write().unwrap().remove(handle);
+        assert!(!body.is_alive());
+        assert!(body.try_borrow().is_none());
+    }
+
+    #[test]
+    fn borrow_blocks_on_other_threads() {
+        let body = RefBody::from(Body::new(
+            1.0,
+            Vector::zero(),
+            Vector::zero(),
+            0.0,
+            0.0,
+            None,
+            None,
+        ));
+        let mut borrowed = body.borrow_mut();
+        let moved = body.clone();
+        let thread = std::thread::spawn(move || moved.position());
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        borrowed.position = Vector::new(3.0, 4.0);
+        drop(borrowed);
+        assert_eq!(thread.join().unwrap(), Vector::new(3.0, 4.0));
+
+        let _position = body.borrow();
+        let moved = body.clone();
+        assert_eq!(
+            std::thread::spawn(move || moved.position()).join().unwrap(),
+            Vector::new(3.0, 4.0)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "already borrowed")]
+    fn nested_borrow_panics() {
+        let body = RefBody::from(Body::new(
+            1.0,
+            Vector::zero(),
+            Vector::zero(),
+            0.0,
+            0.0,
+            None,
+            None,
+        ));
+        let _position = body.borrow();
+        body.apply_impulse(&Vector::new(1.0, 0.0));
+    }
 }
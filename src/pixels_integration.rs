@@ -1,7 +1,13 @@
+use std::{error::Error, time::Instant};
+
 use pixels::{Pixels, SurfaceTexture};
-use winit::window::Window;
+use winit::{
+    event::{Event, WindowEvent},
+    event_loop::EventLoop,
+    window::Window,
+};
 
-use crate::{buffer::Buffer, canvas::Canvas, PhysicalSize, Res};
+use crate::{buffer::Buffer, canvas::Canvas, world::World, PhysicalSize, Res};
 
 impl Buffer for Pixels {
     fn buffer<'a>(&'a mut self) -> &'a mut [u8] {
@@ -36,3 +42,151 @@ impl Canvas {
         Ok(Self::new_from_buffer(buffer_size, buffer))
     }
 }
+
+///Splits the time passed between frames into steps of a fixed `timestep`.
+///
+///The time left over is kept for the next frame and describes how far the simulation is into the next step.
+#[derive(Debug, Clone)]
+pub struct FixedTimestep {
+    timestep: f64,
+    accumulator: f64,
+    max_steps: u32,
+}
+impl FixedTimestep {
+    ///Creates a FixedTimestep with steps of `timestep` seconds. At most 8 steps are taken per frame
+    pub fn new(timestep: f64) -> Self {
+        Self::with_max_steps(timestep, 8)
+    }
+
+    ///Creates a FixedTimestep taking at most `max_steps` steps per frame. Time beyond that is dropped, so a slow frame does not slow down all following ones
+    pub fn with_max_steps(timestep: f64, max_steps: u32) -> Self {
+        FixedTimestep {
+            timestep,
+            accumulator: 0.0,
+            max_steps,
+        }
+    }
+
+    pub fn timestep(&self) -> f64 {
+        self.timestep
+    }
+
+    ///Adds `elapsed` seconds and returns the number of steps, that have to be simulated
+    pub fn advance(&mut self, elapsed: f64) -> u32 {
+        self.accumulator += elapsed;
+        let steps = (self.accumulator / self.timestep).floor();
+        if steps > self.max_steps as f64 {
+            self.accumulator = 0.0;
+            self.max_steps
+        } else {
+            self.accumulator -= steps * self.timestep;
+            steps as u32
+        }
+    }
+
+    ///Progress into the next step between 0.0 and 1.0. Used to interpolate the rendered state
+    pub fn alpha(&self) -> f64 {
+        (self.accumulator / self.timestep).clamp(0.0, 1.0)
+    }
+}
+
+///Hooks of an application driven by a `Runner`. All of them do nothing by default
+pub trait App {
+    ///Called before every fixed `World::step` of `dt` seconds
+    fn update(&mut self, _world: &mut World, _dt: f64) {}
+
+    ///Called after the bodies are drawn and before the frame is presented. `alpha` is the interpolation progress between the last two steps
+    fn draw(&mut self, _world: &mut World, _alpha: f64) {}
+
+    ///Called with every event of the window, after the `Runner` handled it. The `Runner` already resized the `Renderer` on `WindowEvent::Resized`
+    fn window_event(&mut self, _world: &mut World, _event: &WindowEvent) {}
+
+    ///Called with the `error` of a frame, that could not be presented. The `Runner` exits afterwards
+    fn render_failed(&mut self, _world: &mut World, _error: Box<dyn Error>) {}
+}
+
+///Game loop driving a `World` and an `App` on a winit window
+pub struct Runner {
+    timestep: FixedTimestep,
+}
+impl Runner {
+    ///Creates a Runner stepping the world every `timestep` seconds
+    pub fn new(timestep: f64) -> Self {
+        Runner {
+            timestep: FixedTimestep::new(timestep),
+        }
+    }
+
+    pub fn from_timestep(timestep: FixedTimestep) -> Self {
+        Runner { timestep }
+    }
+
+    ///Runs the event loop until the window is closed.
+    ///
+    ///The `world` is stepped at the fixed timestep and rendered interpolated on every `RedrawRequested`. A resized window resizes the `Renderer` of the `world`, the `app` only has to adjust its camera.
+    pub fn run<A: App + 'static>(
+        mut self,
+        event_loop: EventLoop<()>,
+        window: Window,
+        mut world: World,
+        mut app: A,
+    ) -> ! {
+        let mut last_frame = Instant::now();
+        event_loop.run(move |event, _, control_flow| {
+            control_flow.set_poll();
+
+            match event {
+                Event::WindowEvent { event, .. } => {
+                    match &event {
+                        WindowEvent::CloseRequested => control_flow.set_exit(),
+                        WindowEvent::Resized(size) if size.width > 0 && size.height > 0 => {
+                            world
+                                .renderer
+                                .resize(PhysicalSize::new(size.width, size.height), None);
+                        }
+                        _ => {}
+                    }
+                    app.window_event(&mut world, &event);
+                }
+                Event::MainEventsCleared => {
+                    let now = Instant::now();
+                    let steps = self.timestep.advance((now - last_frame).as_secs_f64());
+                    last_frame = now;
+                    let dt = self.timestep.timestep();
+                    for _ in 0..steps {
+                        app.update(&mut world, dt);
+                        world.step(dt);
+                    }
+                    window.request_redraw();
+                }
+                Event::RedrawRequested(_) => {
+                    let alpha = self.timestep.alpha();
+                    world.draw_interpolated(alpha);
+                    app.draw(&mut world, alpha);
+                    if let Err(err) = world.renderer.render() {
+                        app.render_failed(&mut world, err);
+                        control_flow.set_exit();
+                    }
+                }
+                _ => {}
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_timestep() {
+        let mut timestep = FixedTimestep::with_max_steps(0.25, 4);
+
+        assert_eq!(timestep.advance(0.1), 0);
+        assert!((timestep.alpha() - 0.4).abs() < 1e-9);
+        assert_eq!(timestep.advance(0.5), 2);
+        assert!((timestep.alpha() - 0.4).abs() < 1e-9);
+        assert_eq!(timestep.advance(10.0), 4);
+        assert_eq!(timestep.alpha(), 0.0);
+    }
+}
@@ -1,8 +1,12 @@
 use crate::{
-    camera::Camera, canvas::Canvas, canvas::Drawable, color::Color, math_2d::Vector,
-    position::Position, PhysicalSize, Res,
+    camera::Camera, canvas::Canvas, canvas::Drawable, canvas::LineMode, color::Color,
+    math_2d::Vector, position::Position, PhysicalSize, Res,
 };
 
+mod stroke;
+use stroke::{bounds, ellipse_outline, PixelMask};
+pub use stroke::{LineCap, LineJoin, Stroke};
+
 impl Vector {
     /// Clamps a point into positive space while following edge
     ///
@@ -58,6 +62,7 @@ pub struct Renderer {
     pub camera: Camera,
     pub canvas: Canvas,
     clear_color: Color,
+    stroke: Stroke,
 }
 impl Renderer {
     pub fn new(camera: Camera, canvas: Canvas) -> Self {
@@ -65,6 +70,7 @@ impl Renderer {
             camera,
             canvas,
             clear_color: Color::from_str("black"),
+            stroke: Stroke::new(1.0),
         }
     }
 
@@ -79,6 +85,34 @@ impl Renderer {
     pub fn set_clear_color(&mut self, clear_color: Color) {
         self.clear_color = clear_color
     }
+
+    ///Sets the `Stroke` used by `draw_line`, `draw_rect`, `draw_ellipse` and `draw_polygon`. Its width is given in pixels and stays the same for any camera zoom
+    pub fn set_stroke(&mut self, stroke: Stroke) {
+        self.stroke = stroke
+    }
+    pub fn get_stroke(&self) -> Stroke {
+        self.stroke
+    }
+
+    ///Draws a thick stroke along `points` in the `draw_color`. The points are in Logicalspace
+    fn stroke_path(&mut self, points: &[Vector], closed: bool) {
+        let points: Vec<Vector> = points
+            .iter()
+            .map(|point| self.camera.project(point))
+            .collect();
+        let pieces = self.stroke.pieces(&points, closed);
+        let mut mask = PixelMask::around(&pieces, self.get_width(), self.get_height());
+        for piece in &pieces {
+            mask.add(piece);
+        }
+        self.paint_mask(&mask, &self.canvas.get_draw_color());
+    }
+
+    fn paint_mask(&mut self, mask: &PixelMask, color: &Color) {
+        for (x, y) in mask.pixels() {
+            self.canvas.set_pixel(&Position::new(x, y), color);
+        }
+    }
 }
 
 impl Renderer {
@@ -91,8 +125,16 @@ impl Renderer {
     }
 
     pub fn draw_line(&mut self, start: &Vector, end: &Vector) {
+        if self.stroke.is_thick() {
+            return self.stroke_path(&[start.clone(), end.clone()], false);
+        }
         let mut start_projection = self.camera.project(&start);
         let mut end_projection = self.camera.project(&end);
+        if self.canvas.get_line_mode() == LineMode::AntiAliased {
+            self.canvas
+                .draw_smooth_line(&start_projection, &end_projection);
+            return;
+        }
         let edge = &end_projection - &start_projection;
         if start_projection.x < 0.0 || start_projection.y < 0.0 {
             if !start_projection.clamp_point_along_edge(&edge, true) {
@@ -116,6 +158,10 @@ impl Renderer {
     }
 
     pub fn draw_ellipse(&mut self, center: &Vector, a: u32, b: u32, angel_degree: f64) {
+        if self.stroke.is_thick() {
+            let outline = ellipse_outline(center, a as f64, b as f64, angel_degree);
+            return self.stroke_path(&outline, true);
+        }
         let alpha = angel_degree % 360.0;
         let quarter_turns = (alpha / 90.0).floor();
         let angel_degree = alpha - quarter_turns * 90.0;
@@ -126,16 +172,19 @@ impl Renderer {
             (a, b)
         };
 
-        let a = a as i64;
+        //Sample the outline once per pixel on the screen
+        let zoom = self.camera.get_zoom();
+        let steps = (a as f64 * zoom).round() as i64;
+        let (a, b) = (a as f64, b as f64);
 
         let mut first = true;
         let mut top = Vector::zero();
         let mut bottom = Vector::zero();
 
-        for x in -a..=a {
-            let height = ((1.0 - x.pow(2) as f64 / a.pow(2) as f64) * b.pow(2) as f64).sqrt();
+        for step in -steps..=steps {
+            let x = step as f64 / zoom;
+            let height = ((1.0 - x.powi(2) / a.powi(2)) * b.powi(2)).max(0.0).sqrt();
 
-            let x = x as f64;
             let y = -height;
             if first {
                 bottom = center + Vector::new(x, 0.0).rotate_degree(angel_degree);
@@ -160,8 +209,11 @@ impl Renderer {
 
     ///TODO: FUCKING MYSTICAL STUFF. I CANT DO ANYTHING HERE WITHOUT IT DETONATING. WRITE THIS SHIT BETTER!!!!!
     pub fn fill_ellipse(&mut self, center: &Vector, a: u32, b: u32, angel_degree: f64) {
-        let mut a = a as f64;
-        let mut b = b as f64;
+        //The ellipse is rasterized in Bufferspace, where the camera adds its zoom and rotation
+        let center = &self.camera.project(center);
+        let mut a = a as f64 * self.camera.get_zoom();
+        let mut b = b as f64 * self.camera.get_zoom();
+        let angel_degree = angel_degree + self.camera.get_rotation_deg();
         let mut alpha = angel_degree;
 
         if a < b {
@@ -190,11 +242,12 @@ impl Renderer {
 
         let color = self.canvas.get_fill_color();
 
-        for x in min.x as i32..=max.x as i32 {
-            for y in min.y as i32..=max.y as i32 {
-                let vector = Vector::new(x as f64, y as f64);
-                if is_inside(&vector) {
-                    self.set_pixel(&vector, &color)
+        let max_x = max.x.min(self.get_width() as f64 - 1.0);
+        let max_y = max.y.min(self.get_height() as f64 - 1.0);
+        for x in min.x.max(0.0) as u32..=max_x.max(0.0) as u32 {
+            for y in min.y.max(0.0) as u32..=max_y.max(0.0) as u32 {
+                if is_inside(&Vector::new(x as f64, y as f64)) {
+                    self.canvas.set_pixel(&Position::new(x, y), &color)
                 }
             }
         }
@@ -205,7 +258,18 @@ impl Renderer {
         let w = width as f64 / 2.0;
         let h = height as f64 / 2.0;
 
-        let alpha = angel_degree % 360.0;
+        if self.stroke.is_thick() {
+            let corners: Vec<Vector> = [(-w, -h), (w, -h), (w, h), (-w, h)]
+                .into_iter()
+                .map(|(x, y)| center + Vector::new(x, y).rotate_degree(angel_degree))
+                .collect();
+            return self.stroke_path(&corners, true);
+        }
+
+        //The rect is rasterized in Bufferspace, where the camera adds its zoom and rotation
+        let center = self.camera.project(center);
+        let (w, h) = (w * self.camera.get_zoom(), h * self.camera.get_zoom());
+        let alpha = (angel_degree + self.camera.get_rotation_deg()) % 360.0;
         let quarter_turns = (alpha / 90.0).floor();
         let alpha = alpha - quarter_turns * 90.0;
 
@@ -218,17 +282,23 @@ impl Renderer {
         let main_diagonal = Vector::new(w, h);
         let off_diagonal = Vector::new(-w, h);
 
-        //Basic Corners
-        let bottom_right = (center + &main_diagonal).rotate_degree(alpha);
-        let top_left = (center - &main_diagonal).rotate_degree(alpha);
-        let bottom_left = (center + &off_diagonal).rotate_degree(alpha);
-        let top_right = (center - &off_diagonal).rotate_degree(alpha);
+        //Corners in Bufferspace
+        let bottom_right_projection = &center + main_diagonal.rotate_degree(alpha);
+        let top_left_projection = &center - main_diagonal.rotate_degree(alpha);
+        let bottom_left_projection = &center + off_diagonal.rotate_degree(alpha);
+        let top_right_projection = &center - off_diagonal.rotate_degree(alpha);
 
-        //Projected Corners into Bufferspace
-        let bottom_right_projection = self.camera.project(&bottom_right);
-        let bottom_left_projection = self.camera.project(&bottom_left);
-        let top_left_projection = self.camera.project(&top_left);
-        let top_right_projection = self.camera.project(&top_right);
+        if self.canvas.get_line_mode() == LineMode::AntiAliased {
+            self.canvas
+                .draw_smooth_line(&bottom_right_projection, &bottom_left_projection);
+            self.canvas
+                .draw_smooth_line(&bottom_left_projection, &top_left_projection);
+            self.canvas
+                .draw_smooth_line(&top_left_projection, &top_right_projection);
+            self.canvas
+                .draw_smooth_line(&top_right_projection, &bottom_right_projection);
+            return;
+        }
 
         //RECTANGLE IS COMPLETELY OFF SCREEN
         if bottom_right_projection.y < 0.0 {
@@ -317,7 +387,10 @@ impl Renderer {
         let w = width as f64 / 2.0;
         let h = height as f64 / 2.0;
 
-        let alpha = angel_degree % 360.0;
+        //The rect is rasterized in Bufferspace, where the camera adds its zoom and rotation
+        let center = self.camera.project(center);
+        let (w, h) = (w * self.camera.get_zoom(), h * self.camera.get_zoom());
+        let alpha = (angel_degree + self.camera.get_rotation_deg()) % 360.0;
         let quarter_turns = (alpha / 90.0).floor();
         let alpha = alpha - quarter_turns * 90.0;
 
@@ -330,17 +403,11 @@ impl Renderer {
         let main_diagonal = Vector::new(w, h);
         let off_diagonal = Vector::new(-w, h);
 
-        //Basic Corners
-        let bottom_right = (center + &main_diagonal).rotate_degree(alpha);
-        let top_left = (center - &main_diagonal).rotate_degree(alpha);
-        let bottom_left = (center + &off_diagonal).rotate_degree(alpha);
-        let top_right = (center - &off_diagonal).rotate_degree(alpha);
-
-        //Projected Corners into Bufferspace
-        let bottom_right_projection = self.camera.project(&bottom_right);
-        let bottom_left_projection = self.camera.project(&bottom_left);
-        let top_left_projection = self.camera.project(&top_left);
-        let top_right_projection = self.camera.project(&top_right);
+        //Corners in Bufferspace
+        let bottom_right_projection = &center + main_diagonal.rotate_degree(alpha);
+        let top_left_projection = &center - main_diagonal.rotate_degree(alpha);
+        let bottom_left_projection = &center + off_diagonal.rotate_degree(alpha);
+        let top_right_projection = &center - off_diagonal.rotate_degree(alpha);
 
         //RECTANGLE IS COMPLETELY OFF SCREEN
         if bottom_right_projection.y < 0.0
@@ -404,6 +471,31 @@ impl Renderer {
         }
     }
 
+    ///Draws the closed outline through `points`
+    pub fn draw_polygon(&mut self, points: &[Vector]) {
+        if points.len() < 2 {
+            return points.iter().for_each(|point| self.draw_pixel(point));
+        }
+        if self.stroke.is_thick() {
+            return self.stroke_path(points, true);
+        }
+        for i in 0..points.len() {
+            self.draw_line(&points[i], &points[(i + 1) % points.len()]);
+        }
+    }
+
+    ///Fills the area enclosed by `points` using the even-odd rule, so the polygon may be concave
+    pub fn fill_polygon(&mut self, points: &[Vector]) {
+        let points: Vec<Vector> = points
+            .iter()
+            .map(|point| self.camera.project(point))
+            .collect();
+        let (min, max) = bounds(&points);
+        let mut mask = PixelMask::covering(&min, &max, self.get_width(), self.get_height());
+        mask.add_polygon(&points);
+        self.paint_mask(&mask, &self.canvas.get_fill_color());
+    }
+
     pub fn fill_pixel(&mut self, position: &Vector) {
         self.set_pixel(position, &self.canvas.get_fill_color())
     }
@@ -422,11 +514,19 @@ impl Renderer {
     pub fn set_draw_color(&mut self, color: Color) {
         self.canvas.set_draw_color(color.to_rgba())
     }
+    pub fn get_draw_color(&self) -> Color {
+        self.canvas.get_draw_color()
+    }
 
     pub fn set_fill_color(&mut self, color: Color) {
         self.canvas.set_fill_color(color.to_rgba())
     }
 
+    ///Selects how the outlines of all shapes are rasterized
+    pub fn set_line_mode(&mut self, line_mode: LineMode) {
+        self.canvas.set_line_mode(line_mode)
+    }
+
     pub fn get_height(&self) -> u32 {
         self.canvas.get_height()
     }
@@ -464,4 +564,151 @@ mod tests {
         assert_ne!(255, buffer[index1]);
         assert_ne!(255, buffer[index2]);
     }
+
+    #[test]
+    fn polygon() {
+        let size = PhysicalSize::new(8, 8);
+        let mut renderer = Renderer::new(
+            Camera::new(Vector::zero()),
+            Canvas::new_with_simplebuffer(size),
+        );
+        renderer.clear();
+        let triangle = [
+            Vector::new(0.5, 0.5),
+            Vector::new(6.5, 0.5),
+            Vector::new(0.5, 6.5),
+        ];
+        let is_red = |renderer: &mut Renderer, x: u32, y: u32| {
+            let index = ((size.width * y + x) * 4) as usize;
+            renderer.canvas.as_slice()[index..index + 4]
+                == Color::from_str("red").to_rgba().to_slice()
+        };
+
+        renderer.set_fill_color(Color::from_str("red"));
+        renderer.fill_polygon(&triangle);
+        assert!(is_red(&mut renderer, 1, 1));
+        assert!(is_red(&mut renderer, 3, 3));
+        assert!(!is_red(&mut renderer, 4, 4));
+        assert!(!is_red(&mut renderer, 7, 0));
+
+        renderer.clear();
+        renderer.set_draw_color(Color::from_str("red"));
+        renderer.draw_polygon(&[
+            Vector::new(0.0, 0.0),
+            Vector::new(6.0, 0.0),
+            Vector::new(0.0, 6.0),
+        ]);
+        assert!(is_red(&mut renderer, 0, 0));
+        assert!(is_red(&mut renderer, 3, 3));
+        assert!(!is_red(&mut renderer, 1, 1));
+    }
+
+    #[test]
+    fn golden_shapes() -> Res<()> {
+        let mut renderer = Renderer::new(
+            Camera::new(Vector::zero()),
+            Canvas::new_with_simplebuffer(PhysicalSize::new(64, 64)),
+        );
+        renderer.clear();
+
+        renderer.set_fill_color(Color::from_str("blue"));
+        renderer.fill_rect(&Vector::new(16.0, 16.0), 20, 12, 0.0);
+        renderer.set_draw_color(Color::from_str("red"));
+        renderer.draw_rect(&Vector::new(46.0, 16.0), 20, 12, 0.0);
+        renderer.set_fill_color(Color::from_str("green"));
+        renderer.fill_ellipse(&Vector::new(16.0, 46.0), 12, 8, 0.0);
+        renderer.set_draw_color(Color::from_str("white"));
+        renderer.draw_ellipse(&Vector::new(46.0, 46.0), 8, 12, 0.0);
+        renderer.draw_line(&Vector::new(0.0, 63.0), &Vector::new(63.0, 0.0));
+
+        crate::image::assert_golden_image(
+            &mut renderer.canvas,
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/shapes.png"),
+            0,
+        )
+    }
+
+    #[test]
+    fn golden_anti_aliased_shapes() -> Res<()> {
+        let mut renderer = Renderer::new(
+            Camera::new(Vector::zero()),
+            Canvas::new_with_simplebuffer(PhysicalSize::new(64, 64)),
+        );
+        renderer.clear();
+        renderer.set_line_mode(LineMode::AntiAliased);
+
+        renderer.set_draw_color(Color::from_str("red"));
+        renderer.draw_rect(&Vector::new(16.0, 16.0), 20, 12, 0.0);
+        renderer.set_draw_color(Color::from_str("white"));
+        renderer.draw_ellipse(&Vector::new(46.0, 46.0), 12, 8, 30.0);
+        renderer.draw_line(&Vector::new(0.0, 63.0), &Vector::new(63.0, 20.0));
+
+        crate::image::assert_golden_image(
+            &mut renderer.canvas,
+            concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/golden/anti_aliased_shapes.png"
+            ),
+            0,
+        )
+    }
+
+    #[test]
+    fn golden_camera() -> Res<()> {
+        let mut camera = Camera::new(Vector::zero());
+        camera.set_zoom(2.0);
+        camera.set_rotation_deg(30.0);
+        camera.look_at(&Vector::new(8.0, 8.0), &Vector::new(32.0, 32.0));
+        let mut renderer = Renderer::new(
+            camera,
+            Canvas::new_with_simplebuffer(PhysicalSize::new(64, 64)),
+        );
+        renderer.clear();
+
+        renderer.set_fill_color(Color::from_str("blue"));
+        renderer.fill_rect(&Vector::new(0.0, 0.0), 10, 6, 0.0);
+        renderer.set_draw_color(Color::from_str("red"));
+        renderer.draw_rect(&Vector::new(16.0, 0.0), 10, 6, 0.0);
+        renderer.set_fill_color(Color::from_str("green"));
+        renderer.fill_ellipse(&Vector::new(0.0, 16.0), 6, 4, 0.0);
+        renderer.set_draw_color(Color::from_str("white"));
+        renderer.draw_ellipse(&Vector::new(16.0, 16.0), 4, 6, 0.0);
+        renderer.draw_line(&Vector::new(-8.0, 8.0), &Vector::new(24.0, 8.0));
+
+        let corner = renderer.camera.unproject(&Vector::new(32.0, 32.0));
+        assert!(corner.distance(&Vector::new(8.0, 8.0)) < 1e-9);
+
+        crate::image::assert_golden_image(
+            &mut renderer.canvas,
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/camera.png"),
+            0,
+        )
+    }
+
+    #[test]
+    fn golden_strokes() -> Res<()> {
+        let mut renderer = Renderer::new(
+            Camera::new(Vector::zero()),
+            Canvas::new_with_simplebuffer(PhysicalSize::new(64, 64)),
+        );
+        renderer.clear();
+
+        renderer.set_draw_color(Color::from_str("red"));
+        renderer.set_stroke(Stroke::new(4.0));
+        renderer.draw_rect(&Vector::new(16.0, 16.0), 20, 12, 20.0);
+        renderer.set_stroke(Stroke::new(3.0).join(LineJoin::Bevel));
+        renderer.draw_rect(&Vector::new(46.0, 16.0), 16, 16, 0.0);
+        renderer.set_draw_color(Color::from_str("green"));
+        renderer.set_stroke(Stroke::new(3.0));
+        renderer.draw_ellipse(&Vector::new(16.0, 46.0), 12, 8, 30.0);
+        renderer.set_draw_color(Color::from_str("white"));
+        renderer.set_stroke(Stroke::new(5.0).cap(LineCap::Round));
+        renderer.draw_line(&Vector::new(38.0, 56.0), &Vector::new(56.0, 38.0));
+
+        crate::image::assert_golden_image(
+            &mut renderer.canvas,
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/strokes.png"),
+            0,
+        )
+    }
 }
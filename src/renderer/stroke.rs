@@ -0,0 +1,498 @@
+use std::f64::consts::PI;
+
+use crate::math_2d::Vector;
+
+/// Variants describing how the open ends of a stroked line look
+///
+/// * `Butt`: The stroke ends exactly at the endpoint
+/// * `Round`: The stroke ends in a half circle around the endpoint
+/// * `Square`: The stroke is extended by half its width beyond the endpoint
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum LineCap {
+    Butt,
+    Round,
+    Square,
+}
+
+/// Variants describing how the corners between two stroked segments look
+///
+/// * `Miter`: The outer edges are extended until they meet. Very sharp corners fall back to `Bevel`
+/// * `Round`: The corner is rounded off with a circle
+/// * `Bevel`: The outer edges are connected by a straight line
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum LineJoin {
+    Miter,
+    Round,
+    Bevel,
+}
+
+///Describes how the outlines drawn by a `Renderer` look.
+///
+///The `width` is given in pixels. A `width` of up to 1.0 draws the usual one pixel outlines, where `cap` and `join` have no effect.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Stroke {
+    pub width: f64,
+    pub cap: LineCap,
+    pub join: LineJoin,
+}
+impl Stroke {
+    ///Creates a Stroke of `width` with `LineCap::Butt` and `LineJoin::Miter`
+    pub fn new(width: f64) -> Self {
+        Stroke {
+            width,
+            cap: LineCap::Butt,
+            join: LineJoin::Miter,
+        }
+    }
+
+    pub fn cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    pub fn join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    ///Whether the stroke is thicker than a single pixel
+    pub(crate) fn is_thick(&self) -> bool {
+        self.width > 1.0
+    }
+
+    ///Splits the stroke along `points` into convex pieces, that together cover it. `closed` connects the last point with the first one
+    pub(crate) fn pieces(&self, points: &[Vector], closed: bool) -> Vec<StrokePiece> {
+        let half_width = self.width / 2.0;
+        let mut pieces = vec![];
+        let mut points: Vec<&Vector> = points.iter().collect();
+        points.dedup_by(|point, previous| point.distance(previous) < 1e-9);
+        if closed && points.len() > 1 && points[0].distance(points[points.len() - 1]) < 1e-9 {
+            points.pop();
+        }
+        match points.len() {
+            0 => return pieces,
+            1 => {
+                if self.cap == LineCap::Round {
+                    pieces.push(StrokePiece::Disc(points[0].clone(), half_width));
+                }
+                return pieces;
+            }
+            _ => {}
+        }
+
+        let segments = if closed {
+            points.len()
+        } else {
+            points.len() - 1
+        };
+        for i in 0..segments {
+            let start = points[i];
+            let end = points[(i + 1) % points.len()];
+            let normal = (end - start).normalize().perpendicular() * half_width;
+            pieces.push(StrokePiece::Polygon(vec![
+                start + &normal,
+                end + &normal,
+                end - &normal,
+                start - &normal,
+            ]));
+        }
+
+        let joints = if closed {
+            0..points.len()
+        } else {
+            1..points.len() - 1
+        };
+        for i in joints {
+            let previous = points[(i + points.len() - 1) % points.len()];
+            let point = points[i];
+            let next = points[(i + 1) % points.len()];
+            if let Some(piece) = self.join_piece(previous, point, next) {
+                pieces.push(piece);
+            }
+        }
+
+        if !closed {
+            let last = points.len() - 1;
+            pieces.extend(self.cap_piece(points[0], points[1]));
+            pieces.extend(self.cap_piece(points[last], points[last - 1]));
+        }
+        pieces
+    }
+
+    ///Fills the gap on the outer side of the corner at `point`
+    fn join_piece(&self, previous: &Vector, point: &Vector, next: &Vector) -> Option<StrokePiece> {
+        let half_width = self.width / 2.0;
+        let incoming = (point - previous).normalize();
+        let outgoing = (next - point).normalize();
+        let turn = incoming.cross(&outgoing);
+        if turn.abs() < 1e-12 && &incoming * &outgoing > 0.0 {
+            return None;
+        }
+        if self.join == LineJoin::Round {
+            return Some(StrokePiece::Disc(point.clone(), half_width));
+        }
+
+        // the outer side lies right of the path for left turns and left of it for right turns
+        let side = if turn > 0.0 { -1.0 } else { 1.0 };
+        let incoming_normal = incoming.perpendicular() * (side * half_width);
+        let outgoing_normal = outgoing.perpendicular() * (side * half_width);
+        let bevel = vec![
+            point.clone(),
+            point + &incoming_normal,
+            point + &outgoing_normal,
+        ];
+        if self.join == LineJoin::Bevel {
+            return Some(StrokePiece::Polygon(bevel));
+        }
+
+        let miter_direction = (&incoming_normal + &outgoing_normal).normalize();
+        let cosine = &miter_direction * &incoming_normal / half_width;
+        // Same limit as in SVG: Corners sharper than ~29 degree get beveled
+        if cosine < 0.25 {
+            return Some(StrokePiece::Polygon(bevel));
+        }
+        let miter = point + miter_direction * (half_width / cosine);
+        Some(StrokePiece::Polygon(vec![
+            point.clone(),
+            point + &incoming_normal,
+            miter,
+            point + &outgoing_normal,
+        ]))
+    }
+
+    ///Covers the end of the stroke at `point`, that continues towards `neighbour`
+    fn cap_piece(&self, point: &Vector, neighbour: &Vector) -> Option<StrokePiece> {
+        let half_width = self.width / 2.0;
+        match self.cap {
+            LineCap::Butt => None,
+            LineCap::Round => Some(StrokePiece::Disc(point.clone(), half_width)),
+            LineCap::Square => {
+                let outwards = (point - neighbour).normalize() * half_width;
+                let normal = outwards.perpendicular();
+                Some(StrokePiece::Polygon(vec![
+                    point + &normal,
+                    point + &normal + &outwards,
+                    point - &normal + &outwards,
+                    point - &normal,
+                ]))
+            }
+        }
+    }
+}
+
+///Convex part of a stroke
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) enum StrokePiece {
+    Polygon(Vec<Vector>),
+    ///Disc(center, radius)
+    Disc(Vector, f64),
+}
+impl StrokePiece {
+    ///Smallest and biggest corner of the axis aligned box around the piece
+    fn bounds(&self) -> (Vector, Vector) {
+        match self {
+            StrokePiece::Polygon(points) => bounds(points),
+            StrokePiece::Disc(center, radius) => (
+                center - Vector::scalar(*radius),
+                center + Vector::scalar(*radius),
+            ),
+        }
+    }
+}
+
+///Smallest and biggest corner of the axis aligned box around `points`
+pub(crate) fn bounds(points: &[Vector]) -> (Vector, Vector) {
+    points.iter().fold(
+        (
+            Vector::scalar(f64::INFINITY),
+            Vector::scalar(f64::NEG_INFINITY),
+        ),
+        |(min, max), point| {
+            (
+                Vector::new(min.x.min(point.x), min.y.min(point.y)),
+                Vector::new(max.x.max(point.x), max.y.max(point.y)),
+            )
+        },
+    )
+}
+
+///Points along the outline of an ellipse with the semi axes `a` and `b`, rotated by `degree` around its `center`
+pub(crate) fn ellipse_outline(center: &Vector, a: f64, b: f64, degree: f64) -> Vec<Vector> {
+    let steps = ((PI * (a + b) / 2.0).ceil() as usize).clamp(16, 720);
+    (0..steps)
+        .map(|step| {
+            let t = 2.0 * PI * step as f64 / steps as f64;
+            center + Vector::new(a * t.cos(), b * t.sin()).rotate_degree(degree)
+        })
+        .collect()
+}
+
+///Set of pixels of the canvas, used to rasterize overlapping pieces while coloring every pixel only once.
+///
+///The mask only covers the rectangle of `width` and `height` pixels starting at `left` and `top`, pixels outside of it are never added.
+pub(crate) struct PixelMask {
+    left: u32,
+    top: u32,
+    width: u32,
+    height: u32,
+    pixels: Vec<bool>,
+}
+impl PixelMask {
+    pub(crate) fn new(left: u32, top: u32, width: u32, height: u32) -> Self {
+        PixelMask {
+            left,
+            top,
+            width,
+            height,
+            pixels: vec![false; (width * height) as usize],
+        }
+    }
+
+    ///Creates a mask covering the box from `min` to `max`, clipped to a canvas of `canvas_width` and `canvas_height`
+    pub(crate) fn covering(
+        min: &Vector,
+        max: &Vector,
+        canvas_width: u32,
+        canvas_height: u32,
+    ) -> Self {
+        let left = min.x.ceil().max(0.0);
+        let top = min.y.ceil().max(0.0);
+        let right = max.x.floor().min(canvas_width as f64 - 1.0);
+        let bottom = max.y.floor().min(canvas_height as f64 - 1.0);
+        if right < left || bottom < top {
+            return PixelMask::new(0, 0, 0, 0);
+        }
+        PixelMask::new(
+            left as u32,
+            top as u32,
+            (right - left) as u32 + 1,
+            (bottom - top) as u32 + 1,
+        )
+    }
+
+    ///Creates a mask covering all `pieces`, clipped to a canvas of `canvas_width` and `canvas_height`
+    pub(crate) fn around(pieces: &[StrokePiece], canvas_width: u32, canvas_height: u32) -> Self {
+        let (min, max) = pieces.iter().map(StrokePiece::bounds).fold(
+            (
+                Vector::scalar(f64::INFINITY),
+                Vector::scalar(f64::NEG_INFINITY),
+            ),
+            |(min, max), (piece_min, piece_max)| {
+                (
+                    Vector::new(min.x.min(piece_min.x), min.y.min(piece_min.y)),
+                    Vector::new(max.x.max(piece_max.x), max.y.max(piece_max.y)),
+                )
+            },
+        );
+        PixelMask::covering(&min, &max, canvas_width, canvas_height)
+    }
+
+    ///Range of rows covered by the mask, that lie between `min_y` and `max_y`
+    fn rows(&self, min_y: f64, max_y: f64) -> std::ops::Range<u32> {
+        let start = min_y.ceil().max(self.top as f64);
+        let end = (max_y.floor() + 1.0).min((self.top + self.height) as f64);
+        if end <= start {
+            return 0..0;
+        }
+        start as u32..end as u32
+    }
+
+    pub(crate) fn add(&mut self, piece: &StrokePiece) {
+        match piece {
+            StrokePiece::Polygon(points) => self.add_polygon(points),
+            StrokePiece::Disc(center, radius) => self.add_disc(center, *radius),
+        }
+    }
+
+    ///Adds all pixels, whose center lies inside the polygon given by `points`. Uses the even-odd rule, so the polygon may be concave or self-intersecting
+    pub(crate) fn add_polygon(&mut self, points: &[Vector]) {
+        if points.len() < 3 {
+            return;
+        }
+        let (min_y, max_y) = points
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), point| {
+                (min.min(point.y), max.max(point.y))
+            });
+        let mut crossings = vec![];
+        for y in self.rows(min_y, max_y) {
+            let scanline = y as f64;
+            crossings.clear();
+            for i in 0..points.len() {
+                let start = &points[i];
+                let end = &points[(i + 1) % points.len()];
+                if (start.y <= scanline) != (end.y <= scanline) {
+                    crossings.push(
+                        start.x + (scanline - start.y) * (end.x - start.x) / (end.y - start.y),
+                    );
+                }
+            }
+            crossings.sort_by(f64::total_cmp);
+            for span in crossings.chunks_exact(2) {
+                self.add_span(y, span[0], span[1]);
+            }
+        }
+    }
+
+    ///Adds all pixels, whose center lies inside the disc
+    pub(crate) fn add_disc(&mut self, center: &Vector, radius: f64) {
+        for y in self.rows(center.y - radius, center.y + radius) {
+            let half_span = (radius.powi(2) - (y as f64 - center.y).powi(2))
+                .max(0.0)
+                .sqrt();
+            self.add_span(y, center.x - half_span, center.x + half_span);
+        }
+    }
+
+    fn add_span(&mut self, y: u32, start: f64, end: f64) {
+        let start = start.ceil().max(self.left as f64);
+        let end = end.floor().min((self.left + self.width) as f64 - 1.0);
+        if end < start {
+            return;
+        }
+        let row = ((y - self.top) * self.width) as usize;
+        let start = start as usize - self.left as usize;
+        let end = end as usize - self.left as usize;
+        self.pixels[row + start..=row + end].fill(true);
+    }
+
+    ///All pixels of the mask as `(x, y)`
+    pub(crate) fn pixels(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.pixels
+            .iter()
+            .enumerate()
+            .filter(|(_, inside)| **inside)
+            .map(|(index, _)| {
+                (
+                    self.left + index as u32 % self.width,
+                    self.top + index as u32 / self.width,
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mask_to_string(mask: &PixelMask) -> String {
+        let mut s = String::new();
+        for y in 0..mask.height {
+            for x in 0..mask.width {
+                s.push(if mask.pixels[(y * mask.width + x) as usize] {
+                    '#'
+                } else {
+                    '.'
+                });
+            }
+            s.push('\n');
+        }
+        s
+    }
+
+    #[test]
+    fn polygon_mask() {
+        let mut mask = PixelMask::new(0, 0, 6, 5);
+        mask.add_polygon(&[
+            Vector::new(0.5, 0.0),
+            Vector::new(4.5, 0.0),
+            Vector::new(4.5, 4.0),
+            Vector::new(3.5, 4.0),
+            Vector::new(3.5, 2.0),
+            Vector::new(1.5, 2.0),
+            Vector::new(1.5, 4.0),
+            Vector::new(0.5, 4.0),
+        ]);
+        mask.add_disc(&Vector::new(10.0, 10.0), 1.0);
+
+        assert_eq!(
+            mask_to_string(&mask),
+            ".####.\n.####.\n.#..#.\n.#..#.\n......\n"
+        );
+        assert_eq!(mask.pixels().count(), 12);
+    }
+
+    #[test]
+    fn bounded_mask() {
+        let disc = StrokePiece::Disc(Vector::new(20.0, 30.0), 1.0);
+        let mut mask = PixelMask::around(std::slice::from_ref(&disc), 100, 100);
+        assert_eq!(
+            (mask.left, mask.top, mask.width, mask.height),
+            (19, 29, 3, 3)
+        );
+        mask.add(&disc);
+        mask.add_polygon(&[
+            Vector::new(0.0, 0.0),
+            Vector::new(99.0, 0.0),
+            Vector::new(99.0, 99.0),
+            Vector::new(0.0, 99.0),
+        ]);
+        assert_eq!(mask_to_string(&mask), "###\n###\n###\n");
+        assert!(mask.pixels().any(|pixel| pixel == (20, 31)));
+
+        let clipped =
+            PixelMask::around(&[StrokePiece::Disc(Vector::new(-5.0, 50.0), 8.0)], 100, 100);
+        assert_eq!((clipped.left, clipped.width), (0, 4));
+        let outside = PixelMask::around(&[StrokePiece::Disc(Vector::scalar(-50.0), 8.0)], 100, 100);
+        assert_eq!(outside.pixels.len(), 0);
+    }
+
+    #[test]
+    fn caps() {
+        let points = [Vector::new(0.0, 0.0), Vector::new(10.0, 0.0)];
+
+        let pieces = Stroke::new(4.0).pieces(&points, false);
+        assert_eq!(
+            pieces,
+            vec![StrokePiece::Polygon(vec![
+                Vector::new(0.0, 2.0),
+                Vector::new(10.0, 2.0),
+                Vector::new(10.0, -2.0),
+                Vector::new(0.0, -2.0),
+            ])]
+        );
+
+        let pieces = Stroke::new(4.0).cap(LineCap::Round).pieces(&points, false);
+        assert_eq!(pieces.len(), 3);
+        assert_eq!(pieces[2], StrokePiece::Disc(Vector::new(10.0, 0.0), 2.0));
+
+        let pieces = Stroke::new(4.0).cap(LineCap::Square).pieces(&points, false);
+        match &pieces[1] {
+            StrokePiece::Polygon(points) => {
+                assert!(points.iter().all(|point| point.x == 0.0 || point.x == -2.0))
+            }
+            piece => panic!("Expected a polygon, but got {:?}", piece),
+        }
+    }
+
+    #[test]
+    fn joins() {
+        let square = [
+            Vector::new(0.0, 0.0),
+            Vector::new(10.0, 0.0),
+            Vector::new(10.0, 10.0),
+            Vector::new(0.0, 10.0),
+        ];
+
+        let pieces = Stroke::new(2.0).pieces(&square, true);
+        assert_eq!(pieces.len(), 8);
+        match &pieces[5] {
+            StrokePiece::Polygon(points) => {
+                assert!(points.contains(&Vector::new(11.0, -1.0)), "{:?}", points)
+            }
+            piece => panic!("Expected a polygon, but got {:?}", piece),
+        }
+
+        let pieces = Stroke::new(2.0).join(LineJoin::Bevel).pieces(&square, true);
+        match &pieces[5] {
+            StrokePiece::Polygon(points) => assert_eq!(points.len(), 3),
+            piece => panic!("Expected a polygon, but got {:?}", piece),
+        }
+
+        let pieces = Stroke::new(2.0)
+            .join(LineJoin::Round)
+            .pieces(&square, false);
+        assert_eq!(pieces.len(), 5);
+        assert_eq!(pieces[3], StrokePiece::Disc(Vector::new(10.0, 0.0), 1.0));
+    }
+}
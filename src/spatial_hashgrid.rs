@@ -1,7 +1,6 @@
-use std::{
-    collections::{hash_set::Iter, HashSet},
-    hash::Hash,
-};
+#[cfg(test)]
+use std::collections::hash_set::Iter;
+use std::{collections::HashSet, hash::Hash};
 
 use crate::{math_2d::Vector, PhysicalSize};
 
@@ -34,43 +33,93 @@ where
         }
     }
 
-    fn spatial_hash(&self, position: &Vector) -> usize {
+    ///Column and row of the cell containing `position`. Positions outside of the grid are clamped to the closest cell
+    fn cell_coordinates(&self, position: &Vector) -> (usize, usize) {
         let x = (position.x / self.cell_size.width as f64)
             .ceil()
-            .min(self.grid_size.width - 1.0);
+            .clamp(0.0, self.grid_size.width - 1.0);
         let y = (position.y / self.cell_size.height as f64)
             .ceil()
-            .min(self.grid_size.height - 1.0);
-        ((x + y * self.grid_size.width) as usize).min(self.grid.len() - 1)
+            .clamp(0.0, self.grid_size.height - 1.0);
+        (x as usize, y as usize)
     }
 
-    ///Adds the element to the set corresponding to position.
+    #[cfg(test)]
+    fn spatial_hash(&self, position: &Vector) -> usize {
+        let (x, y) = self.cell_coordinates(position);
+        (x + y * self.grid_size.width as usize).min(self.grid.len() - 1)
+    }
+
+    ///Indices of all cells covered by the rectangle from `min` to `max`, grown by `padding` cells in every direction
+    fn spatial_hashes(&self, min: &Vector, max: &Vector, padding: usize) -> Vec<usize> {
+        let (min_x, min_y) = self.cell_coordinates(min);
+        let (max_x, max_y) = self.cell_coordinates(max);
+        let columns = self.grid_size.width as usize;
+        let rows = self.grid_size.height as usize;
+        let mut indices = vec![];
+        for y in min_y.saturating_sub(padding)..=(max_y + padding).min(rows - 1) {
+            for x in min_x.saturating_sub(padding)..=(max_x + padding).min(columns - 1) {
+                indices.push(x + y * columns);
+            }
+        }
+        indices
+    }
+
+    ///Adds the element to the set corresponding to position. Only used to test the grid, bodies are inserted by area
     ///
     ///Returns whether the value was newly inserted. That is:
     ///
     ///If the set did not previously contain this value, true is returned.
     ///If the set already contained this value, false is returned.
+    #[cfg(test)]
     pub fn insert(&mut self, element: T, position: &Vector) -> bool {
-        let index = self.spatial_hash(&position);
+        let index = self.spatial_hash(position);
         self.grid.get_mut(index).unwrap().insert(element)
     }
 
-    ///Removes the element from the set corresponding to position. Returns whether the element was present in the set.
-    pub fn remove(&mut self, element: &T, position: &Vector) -> bool {
-        let index = self.spatial_hash(&position);
-        self.grid.get_mut(index).unwrap().remove(element)
-    }
-
     ///Returns true if the set contains a value.
+    #[cfg(test)]
     pub fn contains(&mut self, element: &T, position: &Vector) -> bool {
-        let index = self.spatial_hash(&position);
+        let index = self.spatial_hash(position);
         self.grid.get_mut(index).unwrap().contains(element)
     }
 
+    #[cfg(test)]
     pub fn get_cell(&self, position: &Vector) -> Iter<'_, T> {
-        let index = self.spatial_hash(&position);
+        let index = self.spatial_hash(position);
         self.grid.get(index).unwrap().iter()
     }
+
+    ///Adds the element to the sets of all cells covered by the rectangle from `min` to `max`.
+    ///
+    ///Returns whether the value was newly inserted. That is:
+    ///
+    ///If none of the covered sets previously contained this value, true is returned.
+    ///If any of them already contained this value, false is returned.
+    pub fn insert_area(&mut self, element: T, min: &Vector, max: &Vector) -> bool {
+        let mut newly_inserted = true;
+        for index in self.spatial_hashes(min, max, 0) {
+            newly_inserted &= self.grid[index].insert(element.clone());
+        }
+        newly_inserted
+    }
+
+    ///Removes the element from the sets of all cells covered by the rectangle from `min` to `max`. Returns whether the element was present in any of the sets.
+    pub fn remove_area(&mut self, element: &T, min: &Vector, max: &Vector) -> bool {
+        let mut removed = false;
+        for index in self.spatial_hashes(min, max, 0) {
+            removed |= self.grid[index].remove(element);
+        }
+        removed
+    }
+
+    ///Returns all elements inside the cells covered by the rectangle from `min` to `max` and their neighbouring cells. Every element is only returned once
+    pub fn get_neighbourhood(&self, min: &Vector, max: &Vector) -> HashSet<&T> {
+        self.spatial_hashes(min, max, 1)
+            .into_iter()
+            .flat_map(|index| self.grid[index].iter())
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -118,4 +167,34 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn area() {
+        let mut grid = SpatialHashgrid::new(PhysicalSize::new(60, 60), PhysicalSize::new(10, 10));
+
+        let wall = "wall".to_string();
+        let min = Vector::new(0.0, 15.0);
+        let max = Vector::new(40.0, 15.0);
+        assert!(grid.insert_area(wall.clone(), &min, &max));
+        assert!(!grid.insert_area(wall.clone(), &min, &max));
+        for x in 0..4 {
+            assert!(grid.contains(&wall, &Vector::new(x as f64 * 10.0, 15.0)));
+        }
+        assert!(!grid.contains(&wall, &Vector::new(15.0, 35.0)));
+
+        grid.insert("ball".to_string(), &Vector::new(55.0, 55.0));
+        grid.insert("box".to_string(), &Vector::new(45.0, 45.0));
+        let neighbours = grid.get_neighbourhood(&Vector::new(5.0, 5.0), &Vector::new(5.0, 5.0));
+        assert_eq!(neighbours, HashSet::from([&wall]));
+        let neighbours = grid.get_neighbourhood(&Vector::new(55.0, 55.0), &Vector::new(55.0, 55.0));
+        assert_eq!(neighbours.len(), 2);
+        assert!(!neighbours.contains(&wall));
+
+        assert!(grid.remove_area(&wall, &min, &max));
+        assert!(!grid.remove_area(&wall, &min, &max));
+        assert!(grid
+            .get_neighbourhood(&Vector::zero(), &Vector::new(60.0, 60.0))
+            .iter()
+            .all(|element| *element != &wall));
+    }
 }
@@ -1,5 +1,12 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use uuid::Uuid;
+
 use crate::{
-    math_2d::Vector, physics_2d::Body, physics_2d::CollisionLayer, physics_2d::RefBody,
+    math_2d::Vector, physics_2d::Body, physics_2d::BodyHandle, physics_2d::BodyKind,
+    physics_2d::BodySet, physics_2d::CollisionLayer, physics_2d::Contact, physics_2d::ForceField,
+    physics_2d::LockMarker, physics_2d::RaycastHit, physics_2d::RefBody, physics_2d::Shape,
     renderer::Renderer, PhysicalSize, Res,
 };
 
@@ -7,11 +14,100 @@ pub struct CollisionSpecifier {
     collision_layer: usize,
     is_collision_obstacle: Option<bool>,
 }
+impl CollisionSpecifier {
+    ///Specifies the `collision_layer` a body belongs to and whether it is a obstacle or an actor in that layer
+    pub fn new(collision_layer: usize, is_collision_obstacle: bool) -> Self {
+        CollisionSpecifier {
+            collision_layer,
+            is_collision_obstacle: Some(is_collision_obstacle),
+        }
+    }
+
+    ///Specifies only the `collision_layer`. Enough to remove a body, but not to add one
+    pub fn layer(collision_layer: usize) -> Self {
+        CollisionSpecifier {
+            collision_layer,
+            is_collision_obstacle: None,
+        }
+    }
+}
 
+/// Events describing the contact between two bodies, identified by their `uuid`s. The smaller `uuid` always comes first
+///
+/// * `Begin(first, second, contact)`: The bodies started touching during the last `step`
+/// * `Persist(first, second, contact)`: The bodies were already touching before the last `step` and still are
+/// * `End(first, second)`: The bodies stopped touching during the last `step`
+///
+/// The normal of the `Contact` points from the first to the second body.
+#[derive(PartialEq, Debug, Clone)]
+pub enum CollisionEvent {
+    Begin(Uuid, Uuid, Contact),
+    Persist(Uuid, Uuid, Contact),
+    End(Uuid, Uuid),
+}
+impl CollisionEvent {
+    ///The `uuid`s of the bodies taking part in the event
+    pub fn bodies(&self) -> (Uuid, Uuid) {
+        match self {
+            CollisionEvent::Begin(first, second, _)
+            | CollisionEvent::Persist(first, second, _)
+            | CollisionEvent::End(first, second) => (*first, *second),
+        }
+    }
+}
+
+type CollisionCallback = Box<dyn FnMut(&CollisionEvent) + Send>;
+
+///A contact between the bodies of the first and second handle. The normal points from the first to the second body
+type TrackedContact = (BodyHandle, BodyHandle, Contact);
+
+///Thresholds deciding when bodies fall asleep, see `World::set_sleeping`.
+///
+///A body rests while its `velocity` and `rotation_velocity` do not exceed `max_velocity` and `max_rotation_velocity`.
+///Once every body of an island, which are the `Dynamic` bodies touching each other, rested for `time_to_sleep` seconds, the whole island falls asleep.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SleepSettings {
+    pub max_velocity: f64,
+    pub max_rotation_velocity: f64,
+    pub time_to_sleep: f64,
+}
+impl SleepSettings {
+    pub fn new(max_velocity: f64, max_rotation_velocity: f64, time_to_sleep: f64) -> Self {
+        SleepSettings {
+            max_velocity,
+            max_rotation_velocity,
+            time_to_sleep,
+        }
+    }
+}
+impl Default for SleepSettings {
+    fn default() -> Self {
+        SleepSettings::new(1.0, 5.0, 0.5)
+    }
+}
+
+///The simulated and rendered scene.
+///
+///Bodies are stored in a `BodySet` and addressed by `BodyHandle`s, so the `World` is `Send + Sync` and can be moved to a simulation thread.
+///`RefBody`s returned by `add_body`, `pick`, the raycasts and queries share the `BodySet` with the `World`. `pick` and the queries have `_handles` variants returning plain `BodyHandle`s.
 pub struct World {
-    bodies: Vec<RefBody>,
+    bodies: Arc<RwLock<BodySet>>,
     pub renderer: Renderer,
     collision_layers: Vec<CollisionLayer>,
+    ///Symmetric matrix declaring which `collision_layers` collide with each other
+    collision_matrix: Vec<Vec<bool>>,
+    ///Pairs of bodies, that touched each other during the last `step`, by their `uuid`s with the smaller one first
+    contacts: HashMap<(Uuid, Uuid), TrackedContact>,
+    collision_events: Vec<CollisionEvent>,
+    ///Only ever locked through `Mutex::get_mut`, it just makes the callbacks `Sync`
+    collision_callbacks: Vec<Mutex<CollisionCallback>>,
+    ///Position and angle of every body before the last `step`, used to interpolate between steps while rendering
+    previous_states: HashMap<BodyHandle, (Vector, f64)>,
+    ///Acceleration applied to every `Dynamic` body with a positive `mass`
+    gravity: Vector,
+    force_fields: Vec<Box<dyn ForceField>>,
+    ///Bodies never fall asleep for `None`
+    sleep_settings: Option<SleepSettings>,
 }
 impl World {
     /// Creates a new World on the `renderer`. The CollisionLayers will use a SpatialHashGrid with `collision_grid_size` cells in the grid
@@ -31,54 +127,1095 @@ impl World {
         }
         World {
             renderer,
-            bodies: vec![],
+            bodies: Arc::new(RwLock::new(BodySet::new())),
+            collision_matrix: (0..number_of_collision_layers)
+                .map(|layer| {
+                    (0..number_of_collision_layers)
+                        .map(|other| layer == other)
+                        .collect()
+                })
+                .collect(),
             collision_layers,
+            contacts: HashMap::new(),
+            collision_events: vec![],
+            collision_callbacks: vec![],
+            previous_states: HashMap::new(),
+            gravity: Vector::zero(),
+            force_fields: vec![],
+            sleep_settings: None,
+        }
+    }
+
+    ///Advances the simulation by `dt` seconds.
+    ///
+    ///First gravity and the `ForceField`s accelerate every `Dynamic` body with a positive `mass`.
+    ///Then every body, that is not `Static`, is moved according to its velocities and the `CollisionLayer`s are kept in sync with the new positions.
+    ///Afterwards touching bodies collide inside their `CollisionLayer` and with the layers it is set to collide with.
+    ///If sleeping is enabled, islands of resting bodies fall asleep. Then the resulting `CollisionEvent`s are emitted.
+    ///This is meant to be called once per frame by the game loop.
+    ///
+    ///Sleeping bodies are skipped entirely. Their contacts with other bodies, that are not moving either, persist.
+    ///
+    ///The `BodySet` stays locked for writing during the whole step. Borrowing a `RefBody` of the world meanwhile on the same thread, e.g. in a `ForceField`, panics.
+    pub fn step(&mut self, dt: f64) {
+        let marker = LockMarker::new(&self.bodies, true);
+        let mut bodies = self.bodies.write().unwrap_or_else(PoisonError::into_inner);
+        self.previous_states = bodies
+            .iter()
+            .map(|(handle, body)| (handle, (body.position.clone(), body.angle_deg)))
+            .collect();
+        let mut moved = vec![];
+        for (handle, body) in bodies.iter_mut() {
+            if body.kind() == BodyKind::Static || body.is_sleeping() {
+                continue;
+            }
+            if body.is_dynamic() && body.mass > 0.0 {
+                let force = self
+                    .force_fields
+                    .iter()
+                    .fold(&self.gravity * body.mass, |force, field| {
+                        force + field.force(body)
+                    });
+                body.apply_force(&force);
+            }
+            body.integrate(dt);
+            if body.has_collision() {
+                moved.push(handle);
+            }
+        }
+        for handle in moved {
+            for layer in &mut self.collision_layers {
+                if layer.update_body(handle, &bodies) {
+                    break;
+                }
+            }
         }
+        let mut contacts = vec![];
+        for layer in 0..self.collision_layers.len() {
+            if self.collision_matrix[layer][layer] {
+                contacts.append(&mut self.collision_layers[layer].resolve_collisions(&mut bodies));
+            }
+            for other in layer + 1..self.collision_layers.len() {
+                if self.collision_matrix[layer][other] {
+                    let (layers, other_layers) = self.collision_layers.split_at_mut(other);
+                    contacts.append(
+                        &mut layers[layer]
+                            .resolve_collisions_with(&mut other_layers[0], &mut bodies),
+                    );
+                }
+            }
+        }
+        if let Some(settings) = &self.sleep_settings {
+            World::fall_asleep(settings, &mut bodies, &contacts, dt);
+        }
+
+        let mut contacts: Vec<((Uuid, Uuid), TrackedContact)> = contacts
+            .into_iter()
+            .map(|(first, second, contact)| {
+                (
+                    (bodies[first].uuid, bodies[second].uuid),
+                    (first, second, contact),
+                )
+            })
+            .collect();
+        let resting =
+            |handle: &BodyHandle| bodies.get(*handle).is_some_and(|body| !body.is_awake());
+        contacts.extend(
+            self.contacts
+                .iter()
+                .filter(|(_, (first, second, _))| resting(first) && resting(second))
+                .map(|(pair, contact)| (*pair, contact.clone())),
+        );
+        drop(bodies);
+        drop(marker);
+        self.emit_collision_events(contacts);
+    }
+
+    ///Adds `dt` to the resting time of every awake `Dynamic` body and puts the islands, whose bodies all rested long enough, to sleep.
+    ///
+    ///Islands are found by joining the bodies of the `contacts` with a union find. Sensors and bodies, that are not `Dynamic`, do not join islands.
+    fn fall_asleep(
+        settings: &SleepSettings,
+        bodies: &mut BodySet,
+        contacts: &[TrackedContact],
+        dt: f64,
+    ) {
+        let mut parents: HashMap<BodyHandle, BodyHandle> = HashMap::new();
+        let mut resting = HashSet::new();
+        for (handle, body) in bodies.iter_mut() {
+            if body.is_dynamic() && !body.is_sleeping() {
+                parents.insert(handle, handle);
+                let resting_time =
+                    body.rest(dt, settings.max_velocity, settings.max_rotation_velocity);
+                if resting_time >= settings.time_to_sleep {
+                    resting.insert(handle);
+                }
+            }
+        }
+
+        fn root(parents: &mut HashMap<BodyHandle, BodyHandle>, handle: BodyHandle) -> BodyHandle {
+            let parent = parents[&handle];
+            if parent == handle {
+                return handle;
+            }
+            let root = root(parents, parent);
+            parents.insert(handle, root);
+            root
+        }
+        let is_sensor = |handle: &BodyHandle| {
+            bodies[*handle]
+                .collision_body()
+                .is_some_and(|collision_body| collision_body.is_sensor())
+        };
+        for (first, second, _) in contacts {
+            if parents.contains_key(first)
+                && parents.contains_key(second)
+                && !is_sensor(first)
+                && !is_sensor(second)
+            {
+                let (first, second) = (root(&mut parents, *first), root(&mut parents, *second));
+                parents.insert(first, second);
+            }
+        }
+
+        let handles: Vec<BodyHandle> = parents.keys().copied().collect();
+        let mut islands: HashMap<BodyHandle, Vec<BodyHandle>> = HashMap::new();
+        for handle in handles {
+            islands
+                .entry(root(&mut parents, handle))
+                .or_default()
+                .push(handle);
+        }
+        for island in islands.values() {
+            if island.iter().all(|handle| resting.contains(handle)) {
+                for handle in island {
+                    bodies[*handle].fall_asleep();
+                }
+            }
+        }
+    }
+
+    ///Enables bodies falling asleep with the given `settings`, or disables it for `None`, which wakes every body up. Disabled by default
+    pub fn set_sleeping(&mut self, settings: Option<SleepSettings>) {
+        if settings.is_none() {
+            for (_, body) in self.bodies_mut().iter_mut() {
+                body.wake_up();
+            }
+        }
+        self.sleep_settings = settings;
+    }
+    pub fn get_sleeping(&self) -> Option<&SleepSettings> {
+        self.sleep_settings.as_ref()
+    }
+
+    ///Sets the acceleration, that pulls every `Dynamic` body with a positive `mass` during `step`. Defaults to zero
+    pub fn set_gravity(&mut self, gravity: Vector) {
+        self.gravity = gravity
+    }
+    pub fn get_gravity(&self) -> &Vector {
+        &self.gravity
+    }
+
+    ///Adds a `ForceField`, that acts on every `Dynamic` body with a positive `mass` during `step`
+    pub fn add_force_field(&mut self, force_field: impl ForceField + 'static) {
+        self.force_fields.push(Box::new(force_field));
+    }
+
+    ///Removes all `ForceField`s. Gravity is kept
+    pub fn clear_force_fields(&mut self) {
+        self.force_fields.clear();
+    }
+
+    ///Declares whether the bodies of `layer` collide with the ones of `other_layer`.
+    ///
+    ///By default every `CollisionLayer` only collides with itself. Passing the same layer twice toggles the collisions inside of it.
+    pub fn set_layer_collision(&mut self, layer: usize, other_layer: usize, collide: bool) {
+        self.collision_matrix[layer][other_layer] = collide;
+        self.collision_matrix[other_layer][layer] = collide;
+    }
+
+    ///Returns whether the bodies of `layer` collide with the ones of `other_layer`
+    pub fn layers_collide(&self, layer: usize, other_layer: usize) -> bool {
+        self.collision_matrix[layer][other_layer]
+    }
+
+    ///Compares the `contacts` of the current `step` with the ones of the previous one, queues the resulting `CollisionEvent`s and passes them to the registered callbacks
+    fn emit_collision_events(&mut self, contacts: Vec<((Uuid, Uuid), TrackedContact)>) {
+        let previous_contacts = std::mem::take(&mut self.contacts);
+        let mut events = vec![];
+        for ((first, second), (first_handle, second_handle, mut contact)) in contacts {
+            let (pair, handles) = if first < second {
+                ((first, second), (first_handle, second_handle))
+            } else {
+                contact.normal = -contact.normal;
+                ((second, first), (second_handle, first_handle))
+            };
+            if self.contacts.contains_key(&pair) {
+                continue;
+            }
+            if previous_contacts.contains_key(&pair) {
+                events.push(CollisionEvent::Persist(pair.0, pair.1, contact.clone()));
+            } else {
+                events.push(CollisionEvent::Begin(pair.0, pair.1, contact.clone()));
+            }
+            self.contacts.insert(pair, (handles.0, handles.1, contact));
+        }
+        for pair in previous_contacts.keys() {
+            if !self.contacts.contains_key(pair) {
+                events.push(CollisionEvent::End(pair.0, pair.1));
+            }
+        }
+
+        for event in &events {
+            for callback in &mut self.collision_callbacks {
+                (callback.get_mut().unwrap_or_else(PoisonError::into_inner))(event);
+            }
+        }
+        self.collision_events.append(&mut events);
+    }
+
+    ///Registers a `callback`, that gets called with every `CollisionEvent` emitted during `step`
+    pub fn on_collision(&mut self, callback: impl FnMut(&CollisionEvent) + Send + 'static) {
+        self.collision_callbacks
+            .push(Mutex::new(Box::new(callback)));
+    }
+
+    ///Removes and returns all `CollisionEvent`s emitted since the last call.
+    ///
+    ///Events are queued until they are drained, so this should be called after every `step`, if the events are used.
+    pub fn drain_collision_events(&mut self) -> std::vec::Drain<'_, CollisionEvent> {
+        self.collision_events.drain(..)
+    }
+
+    ///Returns all bodies containing `point` in Logicalspace, the one drawn last first.
+    ///
+    ///Combined with `Camera::unproject` this finds the bodies under the cursor.
+    pub fn pick(&self, point: &Vector) -> Vec<RefBody> {
+        self.ref_bodies(self.pick_handles(point))
+    }
+
+    ///Like `pick`, but returns the `BodyHandle`s of the bodies
+    pub fn pick_handles(&self, point: &Vector) -> Vec<BodyHandle> {
+        let mut picked: Vec<BodyHandle> = self
+            .bodies()
+            .iter()
+            .filter(|(_, body)| body.contains(point))
+            .map(|(handle, _)| handle)
+            .collect();
+        picked.reverse();
+        picked
+    }
+
+    ///Casts a ray from `origin` along `direction` for at most `max_distance` and returns the first body it hits.
+    ///
+    ///Only the `CollisionLayer`s listed in `layers` are tested, or all of them for `None`. Bodies are hit on the outline of their `CollisionBody`.
+    pub fn raycast(
+        &self,
+        origin: &Vector,
+        direction: &Vector,
+        max_distance: f64,
+        layers: Option<&[usize]>,
+    ) -> Option<RaycastHit> {
+        self.raycast_all(origin, direction, max_distance, layers)
+            .into_iter()
+            .next()
+    }
+
+    ///Like `raycast`, but returns every hit sorted by the distance to `origin`
+    pub fn raycast_all(
+        &self,
+        origin: &Vector,
+        direction: &Vector,
+        max_distance: f64,
+        layers: Option<&[usize]>,
+    ) -> Vec<RaycastHit> {
+        let bodies = self.bodies();
+        let mut hits: Vec<RaycastHit<BodyHandle>> = self
+            .collision_layers
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| layers.is_none_or(|layers| layers.contains(index)))
+            .flat_map(|(_, layer)| layer.raycast(&bodies, origin, direction, max_distance))
+            .collect();
+        hits.sort_by(|hit, other| hit.distance.total_cmp(&other.distance));
+        hits.into_iter()
+            .map(|hit| RaycastHit {
+                body: RefBody::from_handle(hit.body, self.bodies.clone()),
+                point: hit.point,
+                normal: hit.normal,
+                distance: hit.distance,
+            })
+            .collect()
+    }
+
+    ///Returns the bodies, whose `CollisionBody` overlaps the filled `shape` at `position` turned by `angle_deg`, in no particular order.
+    ///
+    ///Only the `CollisionLayer`s listed in `layers` are tested, or all of them for `None`.
+    pub fn query_shape(
+        &self,
+        shape: &Shape,
+        position: &Vector,
+        angle_deg: f64,
+        layers: Option<&[usize]>,
+    ) -> Vec<RefBody> {
+        self.ref_bodies(self.query_shape_handles(shape, position, angle_deg, layers))
+    }
+
+    ///Like `query_shape`, but returns the `BodyHandle`s of the bodies
+    pub fn query_shape_handles(
+        &self,
+        shape: &Shape,
+        position: &Vector,
+        angle_deg: f64,
+        layers: Option<&[usize]>,
+    ) -> Vec<BodyHandle> {
+        let bodies = self.bodies();
+        self.collision_layers
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| layers.is_none_or(|layers| layers.contains(index)))
+            .flat_map(|(_, layer)| layer.query_shape(&bodies, shape, position, angle_deg))
+            .collect()
+    }
+
+    ///Returns the bodies, whose `CollisionBody` overlaps the axis aligned rectangle from `min` to `max`. Works like `query_shape`
+    pub fn query_aabb(&self, min: &Vector, max: &Vector, layers: Option<&[usize]>) -> Vec<RefBody> {
+        self.ref_bodies(self.query_aabb_handles(min, max, layers))
+    }
+
+    ///Like `query_aabb`, but returns the `BodyHandle`s of the bodies
+    pub fn query_aabb_handles(
+        &self,
+        min: &Vector,
+        max: &Vector,
+        layers: Option<&[usize]>,
+    ) -> Vec<BodyHandle> {
+        let corners = Shape::Polygon(vec![
+            min.clone(),
+            Vector::new(max.x, min.y),
+            max.clone(),
+            Vector::new(min.x, max.y),
+        ]);
+        self.query_shape_handles(&corners, &Vector::zero(), 0.0, layers)
+    }
+
+    fn ref_bodies(&self, handles: Vec<BodyHandle>) -> Vec<RefBody> {
+        handles
+            .into_iter()
+            .map(|handle| RefBody::from_handle(handle, self.bodies.clone()))
+            .collect()
     }
 
     pub fn render(&mut self) -> Res<()> {
+        self.draw_interpolated(1.0);
+        self.renderer.render()
+    }
+
+    ///Clears the renderer and draws all bodies at their state interpolated between the last two `step`s, without presenting the result.
+    ///
+    ///`alpha` is the progress from the state before the last `step` (0.0) to the current one (1.0).
+    pub fn draw_interpolated(&mut self, alpha: f64) {
         self.renderer.clear();
-        for body in &self.bodies {
-            body.borrow().render(&mut self.renderer);
+        let bodies = self.bodies.read().unwrap_or_else(PoisonError::into_inner);
+        for (handle, body) in bodies.iter() {
+            let (position, angle) =
+                World::interpolate(body, self.previous_states.get(&handle), alpha);
+            body.render_at(&mut self.renderer, &position, angle);
+        }
+    }
+
+    ///Position and angle of the body of `handle`, interpolated just like it is drawn by `draw_interpolated`
+    pub fn interpolated_state(&self, handle: BodyHandle, alpha: f64) -> Option<(Vector, f64)> {
+        let bodies = self.bodies();
+        let body = bodies.get(handle)?;
+        Some(World::interpolate(
+            body,
+            self.previous_states.get(&handle),
+            alpha,
+        ))
+    }
+
+    fn interpolate(
+        body: &Body<Vector>,
+        previous: Option<&(Vector, f64)>,
+        alpha: f64,
+    ) -> (Vector, f64) {
+        match previous {
+            Some((previous_position, previous_angle)) if alpha < 1.0 => {
+                let position = previous_position + (&body.position - previous_position) * alpha;
+                let turn = (body.angle_deg - previous_angle + 180.0).rem_euclid(360.0) - 180.0;
+                let angle = (previous_angle + turn * alpha).rem_euclid(360.0);
+                (position, angle)
+            }
+            _ => (body.position.clone(), body.angle_deg),
         }
-        self.renderer.render()
     }
 
+    ///Adds the `body` to the world and returns the `RefBody` it is stored as. Its `handle` addresses the body in `bodies`
     pub fn add_body(
         &mut self,
         body: Body<Vector>,
         collision_specifier: Option<CollisionSpecifier>,
-    ) {
+    ) -> RefBody {
         let has_collision = body.has_collision();
-        let body: RefBody = body.into();
-        self.bodies.push(body.clone());
+        let mut bodies = self.bodies.write().unwrap_or_else(PoisonError::into_inner);
+        let handle = bodies.insert(body);
         if has_collision {
             if let Some(specifier) = collision_specifier {
-                self.collision_layers[specifier.collision_layer]
-                    .add_body(body, specifier.is_collision_obstacle.unwrap());
+                self.collision_layers[specifier.collision_layer].add_body(
+                    handle,
+                    &bodies,
+                    specifier.is_collision_obstacle.unwrap(),
+                );
             }
         };
+        RefBody::from_handle(handle, self.bodies.clone())
     }
 
-    pub fn remove_body(&mut self, body: &RefBody, collision_specifier: Option<CollisionSpecifier>) {
-        let has_collision = body.has_collision();
-        if has_collision {
-            if let Some(specifier) = collision_specifier {
+    ///Removes the body from the world and its `CollisionLayer` and returns it.
+    ///
+    ///A `RefBody`, that does not share the `BodySet` of the world, is rejected and `None` is returned.
+    pub fn remove_body(
+        &mut self,
+        body: &RefBody,
+        collision_specifier: Option<CollisionSpecifier>,
+    ) -> Option<Body<Vector>> {
+        if !body.belongs_to(&self.bodies) {
+            return None;
+        }
+        self.remove_body_handle(body.handle(), collision_specifier)
+    }
+
+    ///Like `remove_body`, but addresses the body by its `BodyHandle`
+    pub fn remove_body_handle(
+        &mut self,
+        handle: BodyHandle,
+        collision_specifier: Option<CollisionSpecifier>,
+    ) -> Option<Body<Vector>> {
+        match collision_specifier {
+            Some(specifier) => {
                 self.collision_layers[specifier.collision_layer]
-                    .remove_body(body, specifier.is_collision_obstacle);
-            } else {
+                    .remove_body(handle, specifier.is_collision_obstacle);
+            }
+            None => {
                 for layer in &mut self.collision_layers {
-                    if layer.remove_body(body, None) {
+                    if layer.remove_body(handle, None) {
                         break;
                     }
                 }
             }
         }
-        match self.bodies.iter().position(|el| el == body) {
-            None => {}
-            Some(index) => {
-                self.bodies.remove(index);
+        self.previous_states.remove(&handle);
+        self.bodies_mut().remove(handle)
+    }
+
+    ///Locks the `BodySet` of the world for reading
+    pub fn bodies(&self) -> RwLockReadGuard<'_, BodySet> {
+        self.bodies.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    ///Locks the `BodySet` of the world for writing. Bodies added or removed here bypass the `CollisionLayer`s
+    pub fn bodies_mut(&self) -> RwLockWriteGuard<'_, BodySet> {
+        self.bodies.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    ///Returns a `RefBody` sharing the body of `handle`, if it is part of the world
+    pub fn ref_body(&self, handle: BodyHandle) -> Option<RefBody> {
+        self.bodies()
+            .contains(handle)
+            .then(|| RefBody::from_handle(handle, self.bodies.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        camera::Camera,
+        canvas::Canvas,
+        physics_2d::{BodyBuilder, BodyKind, CollisionBody, Mass, Material, WindZone},
+    };
+
+    use super::*;
+
+    fn world() -> World {
+        let renderer = Renderer::new(
+            Camera::new(Vector::zero()),
+            Canvas::new_with_simplebuffer(PhysicalSize::new(100, 100)),
+        );
+        World::new(renderer, PhysicalSize::new(10, 10), 1)
+    }
+
+    #[test]
+    fn step() {
+        let mut world = world();
+        let body = world.add_body(
+            BodyBuilder::new()
+                .velocity(Vector::new(20.0, 10.0))
+                .rotation_velocity(90.0)
+                .build(),
+            None,
+        );
+
+        world.step(0.5);
+        assert_eq!(body.position(), Vector::new(10.0, 5.0));
+        assert_eq!(body.angle_deg(), 45.0);
+    }
+
+    #[test]
+    fn step_keeps_collision_layers_in_sync() {
+        let mut world = world();
+        let body = world.add_body(
+            BodyBuilder::new()
+                .velocity(Vector::new(40.0, 40.0))
+                .collision(CollisionBody::new(
+                    Shape::Ellipse(Vector::zero(), 5, 5),
+                    Mass::Copy,
+                ))
+                .build(),
+            Some(CollisionSpecifier::new(0, false)),
+        );
+
+        world.step(1.0);
+        world.step(1.0);
+        assert_eq!(body.position(), Vector::scalar(80.0));
+        assert!(world.collision_layers[0].remove_body(body.handle(), None));
+    }
+
+    #[test]
+    fn interpolated_state() {
+        let mut world = world();
+        let body = world.add_body(
+            BodyBuilder::new()
+                .velocity(Vector::new(10.0, 0.0))
+                .rotation_velocity(-20.0)
+                .build(),
+            None,
+        );
+
+        world.step(1.0);
+        let (position, angle) = world.interpolated_state(body.handle(), 0.25).unwrap();
+        assert_eq!(position, Vector::new(2.5, 0.0));
+        assert_eq!(angle, 355.0);
+        assert_eq!(
+            world.interpolated_state(body.handle(), 1.0),
+            Some((Vector::new(10.0, 0.0), 340.0))
+        );
+        world.remove_body(&body, None);
+        assert!(world.interpolated_state(body.handle(), 0.5).is_none());
+    }
+
+    #[test]
+    fn remove_body() {
+        let mut world = world();
+        let body = world.add_body(BodyBuilder::new().build(), None);
+        let stranger = RefBody::from(BodyBuilder::new().build());
+        assert_eq!(stranger.handle(), body.handle());
+
+        assert!(world.remove_body(&stranger, None).is_none());
+        assert!(body.is_alive());
+        assert!(world.remove_body(&body, None).is_some());
+        assert!(!body.is_alive());
+        assert!(stranger.is_alive());
+        assert!(world.remove_body_handle(body.handle(), None).is_none());
+    }
+
+    #[test]
+    fn step_bounces_ball_off_wall() {
+        let mut world = world();
+        let wall = world.add_body(
+            BodyBuilder::new()
+                .position(Vector::new(50.0, 50.0))
+                .collision(CollisionBody::new(
+                    Shape::Line(Vector::new(0.0, -50.0), Vector::new(0.0, 50.0)),
+                    Mass::Infinite,
+                ))
+                .build(),
+            Some(CollisionSpecifier::new(0, true)),
+        );
+        let ball = world.add_body(
+            BodyBuilder::new()
+                .position(Vector::new(30.0, 50.0))
+                .velocity(Vector::new(10.0, 0.0))
+                .collision(CollisionBody::new(
+                    Shape::Ellipse(Vector::zero(), 5, 5),
+                    Mass::Copy,
+                ))
+                .build(),
+            Some(CollisionSpecifier::new(0, false)),
+        );
+
+        for _ in 0..20 {
+            world.step(0.1);
+        }
+        assert!(ball.velocity().distance(&Vector::new(-10.0, 0.0)) < 1e-9);
+        assert!(ball.position().x < 45.0);
+        assert_eq!(wall.velocity(), Vector::zero());
+    }
+
+    #[test]
+    fn collision_events() {
+        let mut world = world();
+        let wall = world.add_body(
+            BodyBuilder::new()
+                .position(Vector::new(50.0, 50.0))
+                .collision(CollisionBody::new(
+                    Shape::Line(Vector::new(0.0, -50.0), Vector::new(0.0, 50.0)),
+                    Mass::Infinite,
+                ))
+                .build(),
+            Some(CollisionSpecifier::new(0, true)),
+        );
+        let ball = world.add_body(
+            BodyBuilder::new()
+                .position(Vector::new(44.0, 50.0))
+                .velocity(Vector::new(10.0, 0.0))
+                .collision(CollisionBody::new(
+                    Shape::Ellipse(Vector::zero(), 5, 5),
+                    Mass::Copy,
+                ))
+                .build(),
+            Some(CollisionSpecifier::new(0, false)),
+        );
+        let pair = {
+            let (wall, ball) = (wall.borrow().uuid, ball.borrow().uuid);
+            if wall < ball {
+                (wall, ball)
+            } else {
+                (ball, wall)
             }
+        };
+
+        let received = Arc::new(Mutex::new(vec![]));
+        let callback_events = received.clone();
+        world.on_collision(move |event| callback_events.lock().unwrap().push(event.clone()));
+
+        world.step(0.2);
+        let events: Vec<CollisionEvent> = world.drain_collision_events().collect();
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], CollisionEvent::Begin(..)));
+        assert_eq!(events[0].bodies(), pair);
+        assert_eq!(world.drain_collision_events().count(), 0);
+
+        world.step(0.0);
+        let events: Vec<CollisionEvent> = world.drain_collision_events().collect();
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], CollisionEvent::Persist(..)));
+
+        world.step(1.0);
+        let events: Vec<CollisionEvent> = world.drain_collision_events().collect();
+        assert_eq!(events, vec![CollisionEvent::End(pair.0, pair.1)]);
+
+        world.step(1.0);
+        assert_eq!(world.drain_collision_events().count(), 0);
+        assert_eq!(received.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn sensor_emits_events() {
+        let mut world = world();
+        world.add_body(
+            BodyBuilder::new()
+                .position(Vector::new(50.0, 50.0))
+                .collision(
+                    CollisionBody::new_solid(Shape::Rect(Vector::zero(), 20, 20), Mass::Infinite)
+                        .sensor(),
+                )
+                .build(),
+            Some(CollisionSpecifier::new(0, true)),
+        );
+        let ball = world.add_body(
+            BodyBuilder::new()
+                .position(Vector::new(30.0, 50.0))
+                .velocity(Vector::new(10.0, 0.0))
+                .collision(CollisionBody::new(
+                    Shape::Ellipse(Vector::zero(), 5, 5),
+                    Mass::Copy,
+                ))
+                .build(),
+            Some(CollisionSpecifier::new(0, false)),
+        );
+
+        world.step(1.0);
+        assert!(matches!(
+            world.drain_collision_events().next(),
+            Some(CollisionEvent::Begin(..))
+        ));
+        world.step(1.0);
+        assert_eq!(ball.position(), Vector::new(50.0, 50.0));
+        assert_eq!(ball.velocity(), Vector::new(10.0, 0.0));
+        assert!(matches!(
+            world.drain_collision_events().next(),
+            Some(CollisionEvent::Persist(..))
+        ));
+    }
+
+    #[test]
+    fn collision_matrix() {
+        let renderer = Renderer::new(
+            Camera::new(Vector::zero()),
+            Canvas::new_with_simplebuffer(PhysicalSize::new(100, 100)),
+        );
+        let mut world = World::new(renderer, PhysicalSize::new(10, 10), 2);
+        assert!(world.layers_collide(0, 0));
+        assert!(!world.layers_collide(0, 1));
+        world.set_layer_collision(0, 0, false);
+        world.set_layer_collision(1, 0, true);
+        assert!(world.layers_collide(0, 1));
+
+        let body = |position: Vector, velocity: Vector| {
+            BodyBuilder::new()
+                .position(position)
+                .velocity(velocity)
+                .collision(CollisionBody::new(
+                    Shape::Rect(Vector::zero(), 10, 10),
+                    Mass::Elastic(1.0),
+                ))
+                .build()
+        };
+        let bullet = world.add_body(
+            body(Vector::new(20.0, 50.0), Vector::new(10.0, 0.0)),
+            Some(CollisionSpecifier::new(0, true)),
+        );
+        let other_bullet = world.add_body(
+            body(Vector::new(25.0, 50.0), Vector::new(10.0, 0.0)),
+            Some(CollisionSpecifier::new(0, true)),
+        );
+        let enemy = world.add_body(
+            body(Vector::new(44.0, 50.0), Vector::zero()),
+            Some(CollisionSpecifier::new(1, false)),
+        );
+
+        world.step(0.1);
+        assert_eq!(world.drain_collision_events().count(), 0);
+        world.step(1.0);
+        let events: Vec<CollisionEvent> = world.drain_collision_events().collect();
+        assert_eq!(events.len(), 1);
+        let (first, second) = events[0].bodies();
+        let enemy_id = enemy.borrow().uuid;
+        let bullet_id = other_bullet.borrow().uuid;
+        assert!(
+            (first, second) == (enemy_id, bullet_id) || (first, second) == (bullet_id, enemy_id)
+        );
+        assert!(enemy.velocity().x > 0.0);
+        assert_eq!(bullet.velocity(), Vector::new(10.0, 0.0));
+    }
+
+    #[test]
+    fn pick() {
+        let mut world = world();
+        world.renderer.camera.set_zoom(2.0);
+        world
+            .renderer
+            .camera
+            .look_at(&Vector::new(50.0, 50.0), &Vector::new(50.0, 50.0));
+        let ground = world.add_body(
+            BodyBuilder::new()
+                .position(Vector::new(50.0, 50.0))
+                .shape(Shape::Rect(Vector::zero(), 40, 40), true)
+                .build(),
+            None,
+        );
+        let ball = world.add_body(
+            BodyBuilder::new()
+                .position(Vector::new(60.0, 50.0))
+                .collision(CollisionBody::new(
+                    Shape::Ellipse(Vector::zero(), 5, 5),
+                    Mass::Infinite,
+                ))
+                .build(),
+            None,
+        );
+        world.add_body(BodyBuilder::new().build(), None);
+
+        let cursor = world.renderer.camera.unproject(&Vector::new(70.0, 50.0));
+        assert_eq!(world.pick(&cursor), vec![ball.clone(), ground.clone()]);
+        assert_eq!(
+            world.pick_handles(&cursor),
+            vec![ball.handle(), ground.handle()]
+        );
+        assert_eq!(world.pick(&Vector::new(35.0, 35.0)), vec![ground]);
+        assert!(world.pick(&Vector::new(10.0, 10.0)).is_empty());
+    }
+
+    #[test]
+    fn raycast() {
+        let renderer = Renderer::new(
+            Camera::new(Vector::zero()),
+            Canvas::new_with_simplebuffer(PhysicalSize::new(100, 100)),
+        );
+        let mut world = World::new(renderer, PhysicalSize::new(10, 10), 2);
+        let wall = |x: f64| {
+            BodyBuilder::new()
+                .position(Vector::new(x, 50.0))
+                .collision(CollisionBody::new(
+                    Shape::Line(Vector::new(0.0, -10.0), Vector::new(0.0, 10.0)),
+                    Mass::Infinite,
+                ))
+                .build()
+        };
+        let near = world.add_body(wall(30.0), Some(CollisionSpecifier::new(1, true)));
+        let far = world.add_body(wall(70.0), Some(CollisionSpecifier::new(0, true)));
+
+        let origin = Vector::new(10.0, 52.0);
+        let direction = Vector::new(2.0, 0.0);
+        let hit = world.raycast(&origin, &direction, 100.0, None).unwrap();
+        assert_eq!(hit.body, near);
+        assert_eq!(hit.point, Vector::new(30.0, 52.0));
+        assert_eq!(hit.normal, Vector::new(-1.0, 0.0));
+        assert_eq!(hit.distance, 20.0);
+
+        let hit = world
+            .raycast(&origin, &direction, 100.0, Some(&[0]))
+            .unwrap();
+        assert_eq!(hit.body, far);
+        assert_eq!(hit.distance, 60.0);
+
+        let hits = world.raycast_all(&origin, &direction, 100.0, None);
+        assert_eq!(
+            hits.into_iter().map(|hit| hit.body).collect::<Vec<_>>(),
+            vec![near, far]
+        );
+        assert!(world.raycast(&origin, &direction, 10.0, None).is_none());
+        assert!(world
+            .raycast(&origin, &Vector::new(0.0, 1.0), 100.0, None)
+            .is_none());
+    }
+
+    #[test]
+    fn query() {
+        let mut world = world();
+        let ball = |position: Vector| {
+            BodyBuilder::new()
+                .position(position)
+                .collision(CollisionBody::new(
+                    Shape::Ellipse(Vector::zero(), 5, 5),
+                    Mass::Infinite,
+                ))
+                .build()
+        };
+        let left = world.add_body(
+            ball(Vector::new(20.0, 50.0)),
+            Some(CollisionSpecifier::new(0, false)),
+        );
+        let right = world.add_body(
+            ball(Vector::new(80.0, 50.0)),
+            Some(CollisionSpecifier::new(0, false)),
+        );
+
+        assert_eq!(
+            world.query_aabb(&Vector::new(0.0, 40.0), &Vector::new(16.0, 60.0), None),
+            vec![left.clone()]
+        );
+        assert!(world
+            .query_aabb(&Vector::new(30.0, 0.0), &Vector::new(70.0, 100.0), None)
+            .is_empty());
+        let mut everything =
+            world.query_aabb_handles(&Vector::zero(), &Vector::new(100.0, 100.0), Some(&[0]));
+        everything.sort();
+        assert_eq!(everything, vec![left.handle(), right.handle()]);
+
+        let circle = Shape::Ellipse(Vector::zero(), 10, 10);
+        assert_eq!(
+            world.query_shape(&circle, &Vector::new(90.0, 55.0), 0.0, None),
+            vec![right.clone()]
+        );
+        assert!(world
+            .query_shape(&circle, &Vector::new(50.0, 50.0), 0.0, None)
+            .is_empty());
+    }
+
+    #[test]
+    fn gravity_and_force_fields() {
+        let mut world = world();
+        world.set_gravity(Vector::new(0.0, 10.0));
+        let falling = world.add_body(BodyBuilder::new().mass(2.0).build(), None);
+        let fixed = world.add_body(BodyBuilder::new().build(), None);
+
+        world.step(0.5);
+        assert_eq!(falling.velocity(), Vector::new(0.0, 5.0));
+        assert_eq!(falling.position(), Vector::new(0.0, 2.5));
+        assert_eq!(fixed.position(), Vector::zero());
+
+        world.add_force_field(WindZone::new(
+            Vector::new(-10.0, 0.0),
+            Vector::new(10.0, 10.0),
+            Vector::new(4.0, 0.0),
+        ));
+        world.add_force_field(|body: &Body<Vector>| Vector::new(0.0, -10.0 * body.mass));
+        world.step(0.5);
+        assert_eq!(falling.velocity(), Vector::new(1.0, 5.0));
+        assert_eq!(fixed.velocity(), Vector::zero());
+
+        world.clear_force_fields();
+        world.set_gravity(Vector::zero());
+        world.step(0.5);
+        assert_eq!(falling.velocity(), Vector::new(1.0, 5.0));
+    }
+
+    #[test]
+    fn body_kinds() {
+        let mut world = world();
+        world.set_gravity(Vector::new(0.0, 10.0));
+        let body = |position: Vector, kind: BodyKind| {
+            BodyBuilder::new()
+                .mass(1.0)
+                .position(position)
+                .velocity(Vector::new(1.0, 0.0))
+                .collision(CollisionBody::new_solid(
+                    Shape::Rect(Vector::zero(), 10, 10),
+                    Mass::Copy,
+                ))
+                .kind(kind)
+                .build()
+        };
+        let level = world.add_body(
+            body(Vector::new(50.0, 80.0), BodyKind::Static),
+            Some(CollisionSpecifier::new(0, true)),
+        );
+        let platform = world.add_body(
+            body(Vector::new(44.0, 70.0), BodyKind::Kinematic),
+            Some(CollisionSpecifier::new(0, true)),
+        );
+        let crate_body = world.add_body(
+            body(Vector::new(20.0, 20.0), BodyKind::Dynamic),
+            Some(CollisionSpecifier::new(0, false)),
+        );
+
+        world.step(1.0);
+        assert_eq!(level.position(), Vector::new(50.0, 80.0));
+        assert_eq!(platform.position(), Vector::new(45.0, 70.0));
+        assert_eq!(platform.velocity(), Vector::new(1.0, 0.0));
+        assert_eq!(crate_body.velocity(), Vector::new(1.0, 10.0));
+        assert!(world.drain_collision_events().next().is_none());
+    }
+
+    #[test]
+    fn friction_stops_sliding_crate() {
+        let mut world = world();
+        world.set_gravity(Vector::new(0.0, 100.0));
+        let material = Material::new(0.0, 0.6, 0.5);
+        world.add_body(
+            BodyBuilder::new()
+                .position(Vector::new(50.0, 80.0))
+                .collision(
+                    CollisionBody::new_solid(Shape::Rect(Vector::zero(), 100, 20), Mass::Infinite)
+                        .material(material.clone()),
+                )
+                .build(),
+            Some(CollisionSpecifier::new(0, true)),
+        );
+        let crate_body = world.add_body(
+            BodyBuilder::new()
+                .mass(1.0)
+                .position(Vector::new(20.0, 64.0))
+                .velocity(Vector::new(20.0, 0.0))
+                .collision(
+                    CollisionBody::new_solid(Shape::Rect(Vector::zero(), 10, 10), Mass::Copy)
+                        .material(material),
+                )
+                .build(),
+            Some(CollisionSpecifier::new(0, false)),
+        );
+
+        for _ in 0..120 {
+            world.step(1.0 / 60.0);
+        }
+        let position = crate_body.position();
+        assert!(crate_body.velocity().x.abs() < 1e-9);
+        assert!(position.x > 20.0 && position.x < 40.0, "{}", position);
+        assert!((position.y - 65.0).abs() < 1.0, "{}", position);
+    }
+
+    #[test]
+    fn sleeping() {
+        let mut world = world();
+        world.set_gravity(Vector::new(0.0, 100.0));
+        world.set_sleeping(Some(SleepSettings::default()));
+        let material = Material::new(0.0, 0.5, 0.5);
+        let ground = world.add_body(
+            BodyBuilder::new()
+                .position(Vector::new(50.0, 80.0))
+                .collision(
+                    CollisionBody::new_solid(Shape::Rect(Vector::zero(), 100, 20), Mass::Infinite)
+                        .material(material.clone()),
+                )
+                .kind(BodyKind::Static)
+                .build(),
+            Some(CollisionSpecifier::new(0, true)),
+        );
+        let crate_at = |x: f64| {
+            BodyBuilder::new()
+                .mass(1.0)
+                .position(Vector::new(x, 65.0))
+                .collision(
+                    CollisionBody::new_solid(Shape::Rect(Vector::zero(), 10, 10), Mass::Copy)
+                        .material(material.clone()),
+                )
+                .build()
+        };
+        let left = world.add_body(crate_at(20.0), Some(CollisionSpecifier::new(0, false)));
+        let right = world.add_body(crate_at(80.0), Some(CollisionSpecifier::new(0, false)));
+
+        for _ in 0..60 {
+            world.step(1.0 / 60.0);
         }
+        assert!(left.is_sleeping());
+        assert!(right.is_sleeping());
+        let resting_position = left.position();
+        world.drain_collision_events();
+
+        world.step(1.0 / 60.0);
+        assert_eq!(left.position(), resting_position);
+        let events: Vec<CollisionEvent> = world.drain_collision_events().collect();
+        assert_eq!(events.len(), 2);
+        assert!(events
+            .iter()
+            .all(|event| matches!(event, CollisionEvent::Persist(..))));
+        let ground_uuid = ground.borrow().uuid;
+        assert!(events
+            .iter()
+            .all(|event| event.bodies().0 == ground_uuid || event.bodies().1 == ground_uuid));
+
+        left.apply_impulse(&Vector::new(0.0, -100.0));
+        assert!(!left.is_sleeping());
+        world.step(1.0 / 60.0);
+        assert!(left.position().y < resting_position.y);
+        assert!(right.is_sleeping());
+
+        world.set_sleeping(None);
+        assert!(!right.is_sleeping());
+    }
+
+    #[test]
+    fn islands_sleep_together() {
+        let mut bodies = BodySet::new();
+        let mut body = |velocity: Vector| {
+            bodies.insert(BodyBuilder::new().mass(1.0).velocity(velocity).build())
+        };
+        let resting = body(Vector::zero());
+        let moving = body(Vector::new(10.0, 0.0));
+        let alone = body(Vector::zero());
+        let contact = Contact {
+            normal: Vector::new(1.0, 0.0),
+            depth: 0.0,
+            points: vec![],
+        };
+
+        let settings = SleepSettings::default();
+        World::fall_asleep(&settings, &mut bodies, &[(resting, moving, contact)], 1.0);
+        assert!(!bodies[resting].is_sleeping());
+        assert!(!bodies[moving].is_sleeping());
+        assert!(bodies[alone].is_sleeping());
+
+        bodies[moving].velocity = Vector::zero();
+        World::fall_asleep(&settings, &mut bodies, &[], 1.0);
+        assert!(bodies[resting].is_sleeping());
+        assert!(bodies[moving].is_sleeping());
+    }
+
+    #[test]
+    fn world_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<World>();
     }
 }